@@ -0,0 +1,167 @@
+// --- Dancing Links による厳密被覆の探索 ---
+// バックトラッキング探索の結果を検証するための別アルゴリズム（Knuth の Algorithm X）
+// 行列の行は呼び出し側が渡す配置パターン（`compute_piece_placements`）から作るので、配置の列挙はバックトラッキング探索と共有している
+// そのため2つの探索の一致で確かめられるのは探索そのものだけで、配置パターンが正しいかどうかはここでは検証できない
+
+/// 厳密被覆問題の疎行列 - ノード0はヘッダの根、1..=列数は列ヘッダ、それ以降が1の要素
+struct Dlx {
+    left: Vec<usize>,
+    right: Vec<usize>,
+    up: Vec<usize>,
+    down: Vec<usize>,
+    column: Vec<usize>,
+    /// 要素が属する行の番号（追加した順、0始まり） - ヘッダは使わない
+    row: Vec<usize>,
+    size: Vec<usize>,
+    /// 追加した行の数
+    row_count: usize,
+}
+
+impl Dlx {
+    /// `column_count`列の空の行列を作る - 全ての列は「ちょうど1回」覆う必要がある
+    fn new(column_count: usize) -> Dlx {
+        let n = column_count + 1;
+        Dlx {
+            left: (0..n).map(|i| if i == 0 { column_count } else { i - 1 }).collect(),
+            right: (0..n).map(|i| if i == column_count { 0 } else { i + 1 }).collect(),
+            up: (0..n).collect(),
+            down: (0..n).collect(),
+            column: (0..n).collect(),
+            row: vec![usize::MAX; n],
+            size: vec![0; n],
+            row_count: 0,
+        }
+    }
+
+    /// 列番号（0始まり）のリストで表した行を追加する
+    fn add_row(&mut self, columns: &[usize]) {
+        let first = self.left.len();
+        for (i, &col) in columns.iter().enumerate() {
+            let header = col + 1;
+            let node = first + i;
+            self.left.push(if i == 0 { first + columns.len() - 1 } else { node - 1 });
+            self.right.push(if i == columns.len() - 1 { first } else { node + 1 });
+            self.up.push(self.up[header]);
+            self.down.push(header);
+            let above = self.up[header];
+            self.down[above] = node;
+            self.up[header] = node;
+            self.column.push(header);
+            self.row.push(self.row_count);
+            self.size[header] += 1;
+        }
+        self.row_count += 1;
+    }
+
+    fn cover(&mut self, header: usize) {
+        self.right[self.left[header]] = self.right[header];
+        self.left[self.right[header]] = self.left[header];
+        let mut row = self.down[header];
+        while row != header {
+            let mut node = self.right[row];
+            while node != row {
+                self.down[self.up[node]] = self.down[node];
+                self.up[self.down[node]] = self.up[node];
+                self.size[self.column[node]] -= 1;
+                node = self.right[node];
+            }
+            row = self.down[row];
+        }
+    }
+
+    fn uncover(&mut self, header: usize) {
+        let mut row = self.up[header];
+        while row != header {
+            let mut node = self.left[row];
+            while node != row {
+                self.size[self.column[node]] += 1;
+                self.down[self.up[node]] = node;
+                self.up[self.down[node]] = node;
+                node = self.left[node];
+            }
+            row = self.up[row];
+        }
+        self.right[self.left[header]] = header;
+        self.left[self.right[header]] = header;
+    }
+
+    /// 厳密被覆の解を全て探索し、見つかるたびに選んだ行の番号のリストを`on_solution`に渡す
+    /// 残りの要素が最も少ない列から分岐する
+    fn search<F: FnMut(&[usize])>(&mut self, chosen_rows: &mut Vec<usize>, on_solution: &mut F) {
+        if self.right[0] == 0 {
+            on_solution(chosen_rows);
+            return;
+        }
+
+        let mut header = self.right[0];
+        let mut best = header;
+        while header != 0 {
+            if self.size[header] < self.size[best] { best = header; }
+            header = self.right[header];
+        }
+        if self.size[best] == 0 { return; }
+
+        self.cover(best);
+        let mut row = self.down[best];
+        while row != best {
+            chosen_rows.push(self.row[row]);
+            let mut node = self.right[row];
+            while node != row { self.cover(self.column[node]); node = self.right[node]; }
+            self.search(chosen_rows, on_solution);
+            let mut node = self.left[row];
+            while node != row { self.uncover(self.column[node]); node = self.left[node]; }
+            chosen_rows.pop();
+            row = self.down[row];
+        }
+        self.uncover(best);
+    }
+}
+
+/// 盤面`start_board`（ビットが立っているマスは埋まっている）の空きマスを、ピースごとの配置パターン
+/// `all_piece_placements`で各ピースちょうど1回ずつ使って埋める厳密被覆問題の行列を作る
+/// 列は「各空きマス」と「各ピース」で、行は穴と重ならない1つの配置パターン
+/// 戻り値の2つ目は、行番号ごとの (ピース番号, 配置ビットマスク)
+fn build_matrix(start_board: u64, all_piece_placements: &[Vec<u64>]) -> (Dlx, Vec<(usize, u64)>) {
+    let empty_cells: Vec<usize> = (0..49).filter(|&bit| start_board & (1u64 << bit) == 0).collect();
+    let mut cell_column = [usize::MAX; 49];
+    for (col, &bit) in empty_cells.iter().enumerate() { cell_column[bit] = col; }
+
+    let mut dlx = Dlx::new(empty_cells.len() + all_piece_placements.len());
+    let mut rows = Vec::new();
+    for (piece_idx, placements) in all_piece_placements.iter().enumerate() {
+        for &placement_mask in placements.iter().filter(|&&mask| mask & start_board == 0) {
+            let mut columns: Vec<usize> = (0..49)
+                .filter(|&bit| placement_mask & (1u64 << bit) != 0)
+                .map(|bit| cell_column[bit])
+                .collect();
+            columns.push(empty_cells.len() + piece_idx);
+            dlx.add_row(&columns);
+            rows.push((piece_idx, placement_mask));
+        }
+    }
+    (dlx, rows)
+}
+
+/// 厳密被覆の解の個数を数える
+pub(crate) fn count_exact_covers(start_board: u64, all_piece_placements: &[Vec<u64>]) -> u32 {
+    let (mut dlx, _) = build_matrix(start_board, all_piece_placements);
+    let mut count = 0;
+    dlx.search(&mut Vec::new(), &mut |_: &[usize]| count += 1);
+    count
+}
+
+/// 厳密被覆の解を全て求める - 各解は、要素iがピースiの配置ビットマスクの配列
+/// 解の並び順はバックトラッキング探索とは異なる
+pub(crate) fn exact_covers(start_board: u64, all_piece_placements: &[Vec<u64>]) -> Vec<Vec<u64>> {
+    let (mut dlx, rows) = build_matrix(start_board, all_piece_placements);
+    let mut solutions = Vec::new();
+    dlx.search(&mut Vec::new(), &mut |chosen_rows: &[usize]| {
+        let mut masks = vec![0; all_piece_placements.len()];
+        for &row in chosen_rows {
+            let (piece_idx, placement_mask) = rows[row];
+            masks[piece_idx] = placement_mask;
+        }
+        solutions.push(masks);
+    });
+    solutions
+}
@@ -4,23 +4,31 @@ use wasm_bindgen::prelude::*;
 
 // SerdeはRustの強力なシリアライズ/デシリアライズフレームワーク
 // ここでは、Rustのデータ構造（struct）をJavaScriptが理解できる形式（JSONのようなJsValue）に変換するために使う
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 // Rustのコードがパニック（実行時エラー）を起こした際に、
 // ブラウザの開発者コンソールに詳細なエラーメッセージを出力してくれる便利なデバッグツール
-use console_error_panic_hook;
+// （`console_error_panic_hook::set_once()` のようにパス指定で呼び出す）
 
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+// 解の個数を検算するための、Dancing Links による別実装の厳密被覆ソルバー - 配置パターンはバックトラッキング探索と共有する
+#[cfg(feature = "dlx")]
+mod dlx;
 
 
 // --- データ構造 ---
 
 /// JavaScript側に渡すための解を表す構造体
 /// #[derive(Serialize)] を付けることで、この構造体をSerdeが自動的にJsValueに変換できるようになる
-#[derive(Serialize)]
-struct Solution {
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Solution {
     /// ピースのID(1-8)で埋められた7x7の盤面 - 日付の穴は-1で表現
-    board: Vec<Vec<i8>>,
+    pub board: Vec<Vec<i8>>,
+    /// 基本形から回転・反転した向きで置かれているピースの数 - 0なら全ピースが基本形のまま置かれた「整った」解
+    /// JavaScriptから解を受け取る場合は省略できる
+    #[serde(default)]
+    pub orientations_used: u8,
 }
 
 /// Union-Find木（素集合データ構造）
@@ -55,13 +63,119 @@ impl UnionFind {
             self.parents[root_y] = root_x as i32;
         }
     }
-    
-    /// 要素xが属するグループのサイズ（要素数）を返す
-    fn size(&mut self, x: usize) -> i32 { let root = self.find(x); -self.parents[root] }
-    
-    /// 全ての根をリストで返す
-    fn roots(&self) -> Vec<usize> { (0..self.n).filter(|&i| self.parents[i] < 0).collect() }
 
+    /// 全要素を再び別々のグループに戻す - 探索の各ノードで作り直さずに使い回すため
+    fn reset(&mut self) { self.parents.fill(-1); }
+
+    /// 要素数をn個に変えてから、全要素を別々のグループに戻す - 確保済みの領域に収まる限り確保し直さない
+    fn reset_with_len(&mut self, n: usize) {
+        self.parents.clear();
+        self.parents.resize(n, -1);
+        self.n = n;
+    }
+
+    /// 全てのグループの (根, サイズ) を返す
+    fn groups(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.parents[..self.n].iter().enumerate().filter(|&(_, &p)| p < 0).map(|(root, &p)| (root, -p as usize))
+    }
+
+}
+
+// --- 盤面の定義 ---
+
+/// 日付に関係なく常に埋まっている（ピースを置けない）固定の穴の座標 (行, 列)
+const FIXED_HOLES: [(usize, usize); 6] = [(0, 6), (1, 6), (6, 3), (6, 4), (6, 5), (6, 6)];
+
+/// 7x7盤面の全マスが埋まった状態のビットマスク
+const FULL_BOARD_MASK: u64 = (1 << 49) - 1;
+
+/// 7x7盤面のレイアウト - 固定の穴と、各月・各日のマスの位置
+/// 市販のパズルには月や日の並びが異なるものがあるので、位置の対応を1か所にまとめておく
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct BoardLayout {
+    /// 日付に関係なく常に埋まっている固定の穴の座標 (行, 列)
+    fixed_holes: &'static [(usize, usize)],
+    /// 要素m-1がm月のマスの座標 (行, 列)
+    month_cells: [(usize, usize); 12],
+    /// 要素d-1がd日のマスの座標 (行, 列)
+    day_cells: [(usize, usize); 31],
+}
+
+impl BoardLayout {
+    /// DragonFjord社の盤面 - 0〜1行目に月が6個ずつ、2〜6行目に日が7個ずつ並ぶ
+    const fn dragon_fjord() -> BoardLayout {
+        let mut month_cells = [(0, 0); 12];
+        let mut i = 0;
+        while i < 12 { month_cells[i] = (i / 6, i % 6); i += 1; }
+        let mut day_cells = [(0, 0); 31];
+        let mut i = 0;
+        while i < 31 { day_cells[i] = (i / 7 + 2, i % 7); i += 1; }
+        BoardLayout { fixed_holes: &FIXED_HOLES, month_cells, day_cells }
+    }
+
+    /// レイアウト名から盤面を選ぶ - 未対応の名前には`None`
+    /// 他社の盤面は、実物の月・日・固定の穴の座標を確かめてから名前を追加する
+    fn from_name(name: &str) -> Option<BoardLayout> {
+        match name {
+            "dragon_fjord" => Some(BoardLayout::dragon_fjord()),
+            _ => None,
+        }
+    }
+
+    /// 月の穴の座標 (行, 列)
+    fn month_cell(&self, month: u32) -> (usize, usize) { self.month_cells[(month - 1) as usize] }
+
+    /// 日の穴の座標 (行, 列)
+    fn day_cell(&self, day: u32) -> (usize, usize) { self.day_cells[(day - 1) as usize] }
+
+    /// 固定の穴と指定された月日の穴を埋めた、探索開始時の盤面を作る
+    fn initial_board(&self, month: u32, day: u32) -> Board {
+        // 1. まず空の盤面を用意
+        let mut board = Board::default();
+
+        // 2. 常に固定の穴をマーク
+        for &(r, c) in self.fixed_holes { board.set(r, c); }
+
+        // 3. 指定された月日の穴をマーク
+        let (month_r, month_c) = self.month_cell(month);
+        board.set(month_r, month_c);
+        let (day_r, day_c) = self.day_cell(day);
+        board.set(day_r, day_c);
+
+        board
+    }
+
+    /// 指定された月日の穴の座標を、解かずにまとめて返す
+    fn hole_positions(&self, month: u32, day: u32) -> HolePositions {
+        let to_u8 = |(r, c): (usize, usize)| (r as u8, c as u8);
+        HolePositions {
+            month_cell: to_u8(self.month_cell(month)),
+            day_cell: to_u8(self.day_cell(day)),
+            fixed: self.fixed_holes.iter().copied().map(to_u8).collect(),
+        }
+    }
+}
+
+impl Default for BoardLayout {
+    fn default() -> Self { BoardLayout::dragon_fjord() }
+}
+
+/// ある日付の盤面の穴の座標 (行, 列) - 解の盤面では`month_cell`と`day_cell`が-1、`fixed`が0になる
+#[derive(Serialize)]
+struct HolePositions {
+    month_cell: (u8, u8),
+    day_cell: (u8, u8),
+    fixed: Vec<(u8, u8)>,
+}
+
+/// 指定された月日の月の穴・日の穴・固定の穴の座標を返す - 「解く」を押す前に盤面の穴を描くために使う
+/// 盤面の位置の計算を`BoardLayout`の1か所にまとめ、フロントエンドで同じ計算を持たなくて済むようにする
+#[wasm_bindgen]
+pub fn get_hole_positions(month: u32, day: u32) -> Result<JsValue, JsValue> {
+    console_error_panic_hook::set_once();
+    validate_date(month, day)?;
+
+    Ok(serde_wasm_bindgen::to_value(&BoardLayout::default().hole_positions(month, day))?)
 }
 
 // --- ピース操作 ---
@@ -81,8 +195,8 @@ fn get_initial_pieces() -> Vec<Vec<Vec<u8>>> {
 }
 
 /// ピースの形状データを回転・反転させる
-fn rotate_and_flip(shape: &Vec<Vec<u8>>, rot_type: u8) -> Vec<Vec<u8>> {
-    let mut current_shape = shape.clone();
+fn rotate_and_flip(shape: &[Vec<u8>], rot_type: u8) -> Vec<Vec<u8>> {
+    let mut current_shape = shape.to_vec();
     // 4以上ならまず左右反転
     if rot_type >= 4 { for row in &mut current_shape { row.reverse(); } }
     let k = rot_type % 4; // 0, 90, 180, 270度の回転
@@ -98,181 +212,4605 @@ fn rotate_and_flip(shape: &Vec<Vec<u8>>, rot_type: u8) -> Vec<Vec<u8>> {
     rotated
 }
 
+/// 形状データから、埋まっているマスを1つも含まない端の行・列を取り除く
+/// 基本形には幅を揃えるための空の列があり、回転・反転すると空の行・列が上下左右のどこにでも来るので、配置を列挙する前に取り除いておく
+fn trim_shape(shape: &[Vec<u8>]) -> Vec<Vec<u8>> {
+    let filled_rows: Vec<usize> = (0..shape.len()).filter(|&i| shape[i].contains(&1)).collect();
+    let filled_cols: Vec<usize> = (0..shape[0].len()).filter(|&j| shape.iter().any(|row| row[j] == 1)).collect();
+    let (Some(&top), Some(&bottom)) = (filled_rows.first(), filled_rows.last()) else { return Vec::new() };
+    let (left, right) = (filled_cols[0], filled_cols[filled_cols.len() - 1]);
+    shape[top..=bottom].iter().map(|row| row[left..=right].to_vec()).collect()
+}
+
 /// ピースの回転・反転から、重複しない形状パターンをすべて生成する
-fn get_unique_rotations(shape: &Vec<Vec<u8>>) -> Vec<Vec<Vec<u8>>> {
+/// 各形状は`trim_shape`で埋まっているマスの外接長方形に切り詰めてある
+fn get_unique_rotations(shape: &[Vec<u8>]) -> Vec<Vec<Vec<u8>>> {
+    unique_orientations(shape, true)
+}
+
+/// `get_unique_rotations`の反転の有無を選べる版 - `allow_flips`がfalseなら回転（rot_type 0〜3）だけを使う
+/// 表裏が区別されていて裏返せないピースの盤面で使う
+fn unique_orientations(shape: &[Vec<u8>], allow_flips: bool) -> Vec<Vec<Vec<u8>>> {
     let mut unique_shapes = Vec::new(); let mut seen = HashSet::new();
-    for i in 0..8 {
-        let rotated = rotate_and_flip(shape, i);
+    for i in 0..if allow_flips { 8 } else { 4 } {
+        let rotated = trim_shape(&rotate_and_flip(shape, i));
         if seen.insert(rotated.clone()) { unique_shapes.push(rotated); }
     }
     unique_shapes
 }
 
-/// 2次元の盤面を64ビット整数（ビットマスク）に変換する
-/// この変換により、非常に高速なビット演算が可能になる
+/// JavaScript側で凡例を描くためのピースの情報
+#[derive(Serialize)]
+struct PieceInfo {
+    /// 盤面で使われるピースのID(1-8)
+    id: u8,
+    /// `get_initial_pieces`の基本形の形状
+    shape: Vec<Vec<u8>>,
+    /// ピースのマス数
+    size: u8,
+    /// 唯一のサイズ6のピースかどうか
+    is_size_6: bool,
+    /// `piece_color`の色名
+    color: &'static str,
+}
+
+/// ピースID(1-8)ごとの (色名, カラーコード) - `static/index.js`の`pieceColors`と同じ並び
+/// `get_initial_pieces`の順（ID = インデックス + 1）に対応する
+const PIECE_COLORS: [(&str, &str); 8] = [
+    ("red", "#E6194B"),
+    ("green", "#3CB44B"),
+    ("yellow", "#FFE119"),
+    ("blue", "#4363D8"),
+    ("orange", "#F58231"),
+    ("purple", "#911EB4"),
+    ("cyan", "#46F0F0"),
+    ("magenta", "#F032E6"),
+];
+
+/// ピースIDの色名を返す - 範囲外のIDには空文字列を返す
+pub fn piece_color(id: u8) -> &'static str {
+    id.checked_sub(1).and_then(|idx| PIECE_COLORS.get(idx as usize)).map_or("", |&(name, _)| name)
+}
+
+/// JavaScript側で凡例を描くためのピースの色
+#[derive(Serialize)]
+struct PieceColor {
+    /// 盤面で使われるピースのID(1-8)
+    id: u8,
+    /// 色名
+    name: &'static str,
+    /// CSSのカラーコード
+    hex: &'static str,
+}
+
+/// 標準の8個のピースのID・基本形・マス数を返す - フロントエンドでピースの形を二重に定義しなくて済むようにする
+/// マス数の合計は41で、固定の穴6個と日付の穴2個を除いた盤面のマス数と一致する
+#[wasm_bindgen]
+pub fn get_pieces() -> Result<JsValue, JsValue> {
+    console_error_panic_hook::set_once();
+
+    Ok(serde_wasm_bindgen::to_value(&piece_infos())?)
+}
+
+/// `get_initial_pieces`の各ピースに、IDとマス数と色を添えた凡例用の情報を作る
+fn piece_infos() -> Vec<PieceInfo> {
+    let size_6_piece_index = find_size_6_piece(&get_initial_pieces());
+    get_initial_pieces().into_iter().enumerate().map(|(piece_idx, shape)| PieceInfo {
+        id: piece_idx as u8 + 1,
+        size: shape.iter().flatten().sum(),
+        shape,
+        is_size_6: Some(piece_idx) == size_6_piece_index,
+        color: piece_color(piece_idx as u8 + 1),
+    }).collect()
+}
+
+/// ピースIDと色の対応表を返す - 盤面の各IDがどの色のピースかを表示するために使う
+#[wasm_bindgen]
+pub fn piece_colors() -> Result<JsValue, JsValue> {
+    console_error_panic_hook::set_once();
+
+    let colors: Vec<PieceColor> = PIECE_COLORS.iter().enumerate()
+        .map(|(idx, &(name, hex))| PieceColor { id: idx as u8 + 1, name, hex })
+        .collect();
+    Ok(serde_wasm_bindgen::to_value(&colors)?)
+}
+
+/// ピースの集合が、固定の穴と日付の2マスを除いた盤面をちょうど埋められる形になっているかを確かめる
+/// マス数の合計が一致すること、サイズ6のピースがちょうど1個あること、各ピースが空の盤面に1通り以上置けることを調べる
+/// 配置パターンの数も`translated_placement_count`と比べ、盤面の端に接する配置が列挙から漏れていないことを確かめる
+fn validate_pieces(pieces: &[Vec<Vec<u8>>]) -> Result<(), String> {
+    let piece_sizes: Vec<usize> = pieces.iter().map(|shape| shape.iter().flatten().filter(|&&cell| cell == 1).count()).collect();
+    let expected_total = 49 - FIXED_HOLES.len() - 2;
+    let total: usize = piece_sizes.iter().sum();
+    if total != expected_total {
+        return Err(format!("ピースのマス数の合計が{}です（{}である必要があります）", total, expected_total));
+    }
+    match piece_sizes.iter().filter(|&&size| size == 6).count() {
+        1 => {}
+        n => return Err(format!("サイズ6のピースがちょうど1個必要ですが、{}個あります", n)),
+    }
+    for (piece_idx, shape) in pieces.iter().enumerate() {
+        let placement_count = shape_placements(shape).len();
+        if placement_count == 0 { return Err(format!("ピース{}を盤面に置ける場所がありません", piece_idx + 1)); }
+        let expected_count = translated_placement_count(shape);
+        if placement_count != expected_count {
+            return Err(format!("ピース{}の配置パターンが{}通りです（{}通りである必要があります）", piece_idx + 1, placement_count, expected_count));
+        }
+    }
+    Ok(())
+}
+
+/// ピースの配置パターンの数を、`shape_placements`とは別の方法で数える
+/// 回転・反転した向きを`normalize_mask`で左上に寄せ、重複しない向きごとに、外接長方形が7x7盤面に収まる平行移動の数を足し合わせる
+fn translated_placement_count(shape: &[Vec<u8>]) -> usize {
+    let orientations: HashSet<u64> = (0..8).map(|rot_type| normalize_mask(Board::from_grid(&rotate_and_flip(shape, rot_type)).0)).collect();
+    orientations.iter().map(|&mask| {
+        let (height, width) = (0..49).filter(|&i| (mask >> i) & 1 == 1).fold((0, 0), |(h, w), i| (h.max(i / 7 + 1), w.max(i % 7 + 1)));
+        (8 - height) * (8 - width)
+    }).sum()
+}
+
+/// 組み込みのピース集合（`get_initial_pieces`）が盤面をちょうど埋められる形になっているかを確かめる
+/// マス数の合計は41（49マスから固定の穴6個と日付の2マスを除いた数）で、`get_initial_pieces`を編集したときの確認に使う
+pub fn validate_piece_set() -> Result<(), String> {
+    validate_pieces(&get_initial_pieces())
+}
+
+// --- 盤面のビットマスク表現 ---
+
+/// 7x7盤面の埋まり具合を64ビット整数（ビットマスク）で表す型
+/// bit (行 * 7 + 列) が1なら、そのマスは穴かピースで埋まっている
+/// ビットマスクにより、非常に高速なビット演算が可能になる
 /// - ピースが重なっているかの判定 → ビットごとのAND演算 (`&`)
 /// - ピースを盤面に置く操作 → ビットごとのOR演算 (`|`)
+///
 /// これらは2次元配列をループで操作するより桁違いに速い
-fn board_to_bitmask(board: &Vec<Vec<u8>>) -> u64 {
-    let mut mask = 0u64;
-    for (i, row) in board.iter().enumerate() {
-        for (j, &cell) in row.iter().enumerate() {
-            if cell == 1 { mask |= 1 << (i * 7 + j); }
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Board(u64);
+
+impl Board {
+    /// 2次元の盤面（1が埋まっているマス）をビットマスクに変換する
+    fn from_grid(grid: &[Vec<u8>]) -> Self {
+        let mut board = Board::default();
+        for (i, row) in grid.iter().enumerate() {
+            for (j, &cell) in row.iter().enumerate() {
+                if cell == 1 { board.set(i, j); }
+            }
         }
+        board
+    }
+
+    /// ビットマスクを2次元の盤面（1が埋まっているマス）に戻す
+    fn to_grid(self) -> Vec<Vec<u8>> {
+        (0..7).map(|r| (0..7).map(|c| self.get(r, c) as u8).collect()).collect()
     }
-    mask
+
+    /// マス (r, c) を埋める - 7x7盤面の外のマスは埋めずにfalseを返す
+    pub fn set(&mut self, r: usize, c: usize) -> bool {
+        if r >= 7 || c >= 7 { return false; }
+        self.set_bit(r * 7 + c);
+        true
+    }
+
+    /// マス (r, c) が埋まっているかどうか - 7x7盤面の外のマスはfalse
+    pub fn get(&self, r: usize, c: usize) -> bool { r < 7 && c < 7 && (self.0 >> (r * 7 + c)) & 1 == 1 }
+
+    /// bit `bit`のマスを埋める - 7行を超える盤面（曜日つきの盤面など）で (行 * 7 + 列) を直接指定するために使う
+    fn set_bit(&mut self, bit: usize) {
+        debug_assert!(bit < 64, "ビットマスクの範囲外のマスです: {}", bit);
+        self.0 |= 1 << bit;
+    }
+
+    /// 全49マスが埋まっているかどうか
+    pub fn is_full(&self) -> bool { self.0 == FULL_BOARD_MASK }
+
+    /// 空きマスの数
+    pub fn empty_cells(&self) -> u32 { (FULL_BOARD_MASK & !self.0).count_ones() }
+
+    /// 2つの盤面に共通して埋まっているマスがあるかどうか
+    pub fn overlaps(&self, other: &Board) -> bool { (self.0 & other.0) != 0 }
+
+    /// 2つの盤面を重ね合わせた盤面
+    pub fn union(&self, other: &Board) -> Board { Board(self.0 | other.0) }
+}
+
+/// ビットマスクの形状を、最も上の行・最も左の列が盤面の端に来るように平行移動する
+fn normalize_mask(mask: u64) -> u64 {
+    if mask == 0 { return 0; }
+    let min_row = mask.trailing_zeros() / 7;
+    let min_col = (0..64).filter(|&i| (mask >> i) & 1 == 1).map(|i| i % 7).min().unwrap_or(0);
+    mask >> (min_row * 7 + min_col)
+}
+
+/// 配置ビットマスクが、ピースの`get_unique_rotations`の何番目の向きかを返す
+/// 0は基本形（回転・反転なし）の向きで、どの向きとも一致しなければ`None`
+fn orientation_index(shape: &[Vec<u8>], mask: u64) -> Option<usize> {
+    let normalized = normalize_mask(mask);
+    get_unique_rotations(shape).iter().position(|rotated| normalize_mask(Board::from_grid(rotated).0) == normalized)
+}
+
+/// 解のうち、基本形以外の向きで置かれているピースの数を数える
+fn count_orientations_used(masks: &[u64], shapes: &[Vec<Vec<u8>>]) -> u8 {
+    masks.iter().zip(shapes).filter(|&(&mask, shape)| orientation_index(shape, mask).is_some_and(|i| i > 0)).count() as u8
+}
+
+/// `count_orientations_used`の標準のピース集合専用版 - 解ごとに回転・反転を計算し直さず、`PIECE_ORIENTATION_MASKS`の表を引く
+fn standard_orientations_used(masks: &[u64]) -> u8 {
+    masks.iter().zip(PIECE_ORIENTATION_MASKS.iter())
+        .filter(|&(&mask, orientations)| orientations.iter().position(|&oriented| oriented == normalize_mask(mask)).is_some_and(|i| i > 0))
+        .count() as u8
 }
 
 // --- コアアルゴリズム ---
 
 /// 枝刈り（Pruning）判定関数 - 盤面が手詰まりかどうかを調べる
-fn judge_connected_component(board_mask: u64, is_size_6_piece_used: bool) -> bool {
-    let mut uf = UnionFind::new(49);
-    // 空きマス(`0`)をUnion-Findでグループ分けする
-    for i in 0..49 {
-        if (board_mask >> i) & 1 == 0 { // マスが空いているか
-            if (i + 1) % 7 != 0 && (board_mask >> (i + 1)) & 1 == 0 { uf.union(i, i + 1); }
-            if i < 42 && (board_mask >> (i + 7)) & 1 == 0 { uf.union(i, i + 7); }
-        }
+/// `remaining_sizes`はまだ置いていないピースのマス数で、空きマスの各島の大きさがその部分和で作れなければ手詰まりとする
+/// 5〜6マスの島の形を`PIECE_SHAPE_MASKS`と比べるので、標準のピース集合（とその向きを制限したもの）の探索でだけ使う
+/// 独自のピース集合の探索では、島の大きさだけを見る`judge_islands_for_sizes`を使うこと
+fn judge_connected_component(board: Board, remaining_sizes: impl IntoIterator<Item = usize>) -> bool {
+    let board_mask = board.0;
+    // 残りのピースで作れる合計マス数の集合をビット集合で求める（bit kが立っていればkマスを作れる）
+    let mut reachable_sums = 1u64;
+    for size in remaining_sizes {
+        debug_assert!((5..=6).contains(&size), "標準のピース集合にない大きさのピースです: {}", size);
+        reachable_sums |= reachable_sums << size;
     }
-    // 各空きマスグループ（島）のサイズをチェック
-    for root in uf.roots() {
-        if (board_mask >> root) & 1 == 0 {
-            let size = uf.size(root);
-            if size < 5 { return false; } // どのピースよりも小さい島は手詰まり
-            
-            // サイズ6のピースが未使用の場合: 島は5の倍数か、(6 + 5の倍数)で構成可能
-            if !is_size_6_piece_used {
-                if size % 5 != 0 && (size < 6 || (size - 6) % 5 != 0) { return false; }
-            } else { // サイズ6のピースが使用済みの場合: 島は5の倍数でなければならない
-                if size % 5 != 0 { return false; }
+
+    ISLAND_UNION_FIND.with_borrow_mut(|uf| {
+        uf.reset();
+        // 空きマス(`0`)をUnion-Findでグループ分けする
+        for i in 0..49 {
+            if (board_mask >> i) & 1 == 0 { // マスが空いているか
+                if (i + 1) % 7 != 0 && (board_mask >> (i + 1)) & 1 == 0 { uf.union(i, i + 1); }
+                if i < 42 && (board_mask >> (i + 7)) & 1 == 0 { uf.union(i, i + 7); }
             }
         }
+        // 各空きマスグループ（島）のサイズをチェック
+        let mut has_single_piece_island = false;
+        for (root, size) in uf.groups() {
+            if (board_mask >> root) & 1 == 0 {
+                // 残りのピースの組み合わせで作れない大きさの島は手詰まり（どのピースよりも小さい島を含む）
+                if (reachable_sums >> size) & 1 == 0 { return false; }
+                if size <= 6 { has_single_piece_island = true; }
+            }
+        }
+        // 2段目の枝刈り: 標準のピース集合では、5マスか6マスの島はちょうど1個のピースで埋めるしかないので、島の形がピースの形と一致しなければ手詰まり
+        !has_single_piece_island || judge_single_piece_islands(board_mask, uf)
+    })
+}
+
+thread_local! {
+    /// `judge_connected_component`が使い回すUnion-Find木 - 探索の全ノードで確保し直すのを避ける
+    static ISLAND_UNION_FIND: std::cell::RefCell<UnionFind> = std::cell::RefCell::new(UnionFind::new(49));
+}
+
+/// 5マスか6マスの島が全て、いずれかのピースのいずれかの向きと同じ形かどうかを調べる
+/// 島の大きさが足りていても、ピースの集合にない形（T字・十字など）の島は埋められない
+/// 形は標準のピース集合のものと比べるので、標準のピース集合（とその向きを制限したもの）の探索でだけ使う
+fn judge_single_piece_islands(board_mask: u64, uf: &mut UnionFind) -> bool {
+    let mut island_masks = [0u64; 49];
+    for i in 0..49 {
+        if (board_mask >> i) & 1 == 0 { island_masks[uf.find(i)] |= 1u64 << i; }
+    }
+    island_masks.iter()
+        .filter(|&&mask| (5..=6).contains(&mask.count_ones()))
+        .all(|&mask| PIECE_SHAPE_MASKS.contains(&normalize_mask(mask)))
+}
+
+/// 配置パターンを「最も番号の小さいマス」ごとに分類した索引 - 要素iは、bit iが最下位ビットである (ピース番号, 配置ビットマスク) のリスト
+/// 空きマスを番号の小さい順に埋めていく探索では、最初の空きマスより前のマスは全て埋まっているので、
+/// そのマスを覆える配置は、そのマスを最下位ビットに持つものに限られる
+/// 各リストは (ピース番号, 配置ビットマスク) の昇順に並び、これが解の見つかる順序を決める
+fn cell_placement_index(all_piece_placements: &[Vec<u64>]) -> Vec<Vec<(usize, u64)>> {
+    let mut index = vec![Vec::new(); 64];
+    for (piece_idx, placements) in all_piece_placements.iter().enumerate() {
+        for &placement_mask in placements {
+            if placement_mask != 0 { index[placement_mask.trailing_zeros() as usize].push((piece_idx, placement_mask)); }
+        }
     }
-    true // 手詰まりではない
+    index
+}
+
+/// 盤面の空きマスのうち、最も番号（行 * 7 + 列）の小さいマス - 全て埋まっていれば`None`
+fn first_empty_cell(board: Board) -> Option<usize> {
+    let empty = !board.0 & FULL_BOARD_MASK;
+    if empty == 0 { None } else { Some(empty.trailing_zeros() as usize) }
 }
 
 /// バックトラッキング（深さ優先探索）で全解法を探索する再帰関数
-/// 1. ピースを1つ置く
+/// 1. 盤面の最初の空きマスを選び、そのマスを覆える未使用のピースの配置を1つ置く
 /// 2. 盤面が妥当かチェック（枝刈り）
-/// 3. 妥当なら、次のピースのために自分自身を呼び出す（深く潜る）
+/// 3. 妥当なら、次の空きマスのために自分自身を呼び出す（深く潜る）
 /// 4. 探索が終わったら、置いたピースを元に戻し（バックトラック）、別の置き方を試す
-fn find_solutions_recursive(
-    piece_idx: usize, current_board_mask: u64, used_placements: &mut Vec<u64>,
-    is_size_6_piece_used: bool, all_piece_placements: &Vec<Vec<u64>>,
-    size_6_piece_index: usize, solutions: &mut Vec<Vec<u64>>,
-) {
-    // ベースケース: 全8ピースを配置できたら解として保存
-    if piece_idx == 8 { solutions.push(used_placements.clone()); return; }
+///
+/// 毎回「必ず埋めなければならないマス」で分岐するので、同じ配置を別の順序で置き直す重複がなく、分岐数が大幅に減る
+/// `used_placements`の要素iはピースiの配置ビットマスク（未配置なら0）で、全ピースを置いて盤面が埋まったら解になる
+/// 解が見つかるたびに`on_solution`を呼び出す - `on_solution`がfalseを返したら探索全体を打ち切り、falseを返す
+/// `on_node`は探索木の各ノードに入るたびに呼び出し、falseを返したら同様に探索全体を打ち切る（時間制限などに使う）
+/// 索引の配置パターンは決まった順に並んでいるため、解が見つかる順序は常に同じになる
+fn find_solutions_recursive<F: FnMut(&[u64; 8]) -> bool, G: FnMut() -> bool>(
+    current_board: Board, used_placements: &mut [u64; 8], cell_placements: &[Vec<(usize, u64)>],
+    piece_sizes: &[usize; 8], on_solution: &mut F, on_node: &mut G,
+) -> bool {
+    if !on_node() { return false; }
+
+    // ベースケース: 盤面が埋まったら、全8ピースを配置できていれば解として報告
+    let Some(cell) = first_empty_cell(current_board) else {
+        return if used_placements.iter().all(|&mask| mask != 0) { on_solution(used_placements) } else { true };
+    };
+
+    // 最初の空きマスを覆える全ての配置パターンを試す
+    for &(piece_idx, placement_mask) in &cell_placements[cell] {
+        // 高速なビット演算で、ピースが未使用で既存の盤面と重ならないかチェック
+        if used_placements[piece_idx] == 0 && !current_board.overlaps(&Board(placement_mask)) {
+            let new_board = current_board.union(&Board(placement_mask));
+            used_placements[piece_idx] = placement_mask; // ピースを配置
 
-    // 現在のピースの全ての配置パターンを試す
-    for &placement_mask in &all_piece_placements[piece_idx] {
-        // 高速なビット演算で、ピースが既存の盤面と重ならないかチェック
-        if (current_board_mask & placement_mask) == 0 {
-            let new_board_mask = current_board_mask | placement_mask;
-            let new_is_size_6_used = is_size_6_piece_used || (piece_idx == size_6_piece_index);
-            
             // 枝刈り: この配置で手詰まりにならないかチェック
-            if judge_connected_component(new_board_mask, new_is_size_6_used) {
-                used_placements.push(placement_mask); // ピースを配置
-                // 再帰呼び出しで次のピースの探索へ
-                find_solutions_recursive(
-                    piece_idx + 1, new_board_mask, used_placements, new_is_size_6_used,
-                    all_piece_placements, size_6_piece_index, solutions,
-                );
-                used_placements.pop(); // バックトラック: 最後のピース配置を取り消す
+            if judge_connected_component(new_board, unused_piece_sizes(used_placements, piece_sizes)) {
+                // 再帰呼び出しで次の空きマスの探索へ
+                let keep_going = find_solutions_recursive(new_board, used_placements, cell_placements, piece_sizes, on_solution, on_node);
+                if !keep_going { used_placements[piece_idx] = 0; return false; }
             }
+            used_placements[piece_idx] = 0; // バックトラック: ピース配置を取り消す
         }
     }
+    true
 }
 
+/// `find_solutions_recursive`と同じ探索を、再帰の代わりに明示的なスタックで行う反復版
+/// スタックの小さい組み込み向けWASMホストでも、呼び出しスタックを深く消費せずに動作する
+/// 解が見つかる順序と`on_solution`・`on_node`の扱いは再帰版と全く同じ
+fn find_solutions_iterative<F: FnMut(&[u64; 8]) -> bool, G: FnMut() -> bool>(
+    start_board: Board, used_placements: &mut [u64; 8], cell_placements: &[Vec<(usize, u64)>],
+    piece_sizes: &[usize; 8], on_solution: &mut F, on_node: &mut G,
+) -> bool {
+    if !on_node() { return false; }
+    let mut search = IterativeSearch::new(start_board, *used_placements);
+    let finished = search.resume(cell_placements, piece_sizes, on_solution, on_node);
+    *used_placements = search.used_placements;
+    finished
+}
 
-/// WASMとしてJavaScriptに公開されるメイン関数
-/// `#[wasm_bindgen]` アトリビュートにより、このRust関数がJavaScriptから直接呼び出せるようになる
-#[wasm_bindgen]
-pub fn solve_for_date(month: u32, day: u32) -> Result<JsValue, JsValue> {
-    // Rustがパニックした際に、ブラウザのコンソールにエラーを出力する設定
-    console_error_panic_hook::set_once();
+/// 探索途中の1段分の状態 - 再帰版の1回の関数呼び出しに相当する
+/// `placed_piece`はこの段に潜るときに置いたピースの番号（最初の段は`None`）
+struct Frame { board: Board, placed_piece: Option<usize>, next_index: usize }
 
-    // --- 事前計算フェーズ ---
-    // 探索を始める前に、全ピースの全配置パターンを計算しておく
-    // これにより、探索中の回転や重複チェックのコストをなくし、大幅に高速化する
-    let initial_pieces = get_initial_pieces();
-    let piece_sizes: Vec<usize> = initial_pieces.iter().map(|p| p.iter().map(|r| r.iter().sum::<u8>() as usize).sum()).collect();
-    let size_6_piece_index = piece_sizes.iter().position(|&s| s == 6).unwrap();
-
-    let all_piece_placements: Vec<Vec<u64>> = initial_pieces.iter().map(|p_shape| {
-        let unique_shapes = get_unique_rotations(p_shape);
-        let mut placements = HashSet::new();
-        for shape in unique_shapes {
-            let (h, w) = (shape.len(), shape[0].len());
-            for r in 0..(8 - h) {
-                for c in 0..(8 - w) {
-                    let mut board = vec![vec![0; 7]; 7];
-                    let mut is_valid = true;
-                    for (i, row) in shape.iter().enumerate() {
-                        for (j, &cell) in row.iter().enumerate() {
-                            if r + i >= 7 || c + j >= 7 {
-                                if cell == 1 { is_valid = false; }
-                                continue;
-                            };
-                            if cell == 1 { board[r + i][c + j] = 1; }
-                        }
+/// 明示的なスタックによる探索の途中状態 - `resume`が打ち切られても、もう一度`resume`を呼べば続きから探索できる
+struct IterativeSearch {
+    stack: Vec<Frame>,
+    /// 要素iはピースiの現在の配置ビットマスク（未配置なら0）
+    used_placements: [u64; 8],
+}
+
+impl IterativeSearch {
+    /// 盤面`start_board`の根ノードだけを積んだ状態を作る - 根ノードの`on_node`は呼び出し側で扱う
+    fn new(start_board: Board, pinned: [u64; 8]) -> IterativeSearch {
+        IterativeSearch { stack: vec![Frame { board: start_board, placed_piece: None, next_index: 0 }], used_placements: pinned }
+    }
+
+    /// 探索を最後まで終えたかどうか
+    fn is_finished(&self) -> bool {
+        self.stack.is_empty()
+    }
+
+    /// 探索を進める - 最後まで終えたらtrue、`on_solution`か`on_node`がfalseを返して中断したらfalseを返す
+    /// 中断はスタックが整合した状態で行うため、同じ索引で再び呼び出すと、解を重複も欠落もなく続きから報告する
+    fn resume<F: FnMut(&[u64; 8]) -> bool, G: FnMut() -> bool>(
+        &mut self, cell_placements: &[Vec<(usize, u64)>], piece_sizes: &[usize; 8], on_solution: &mut F, on_node: &mut G,
+    ) -> bool {
+        let used_placements = &mut self.used_placements;
+        let stack = &mut self.stack;
+        while let Some(frame) = stack.last_mut() {
+            let Some(cell) = first_empty_cell(frame.board) else {
+                // ベースケース: 盤面が埋まったら1段戻り、全8ピースを配置できていれば解として報告する
+                let solution = *used_placements;
+                if let Some(piece_idx) = frame.placed_piece { used_placements[piece_idx] = 0; }
+                stack.pop();
+                if solution.iter().all(|&mask| mask != 0) && !on_solution(&solution) { return false; }
+                continue;
+            };
+
+            // 現在の段で、まだ試していない配置パターンから次の子ノードを探す
+            let mut child = None;
+            while frame.next_index < cell_placements[cell].len() {
+                let (piece_idx, placement_mask) = cell_placements[cell][frame.next_index];
+                frame.next_index += 1;
+                if used_placements[piece_idx] == 0 && !frame.board.overlaps(&Board(placement_mask)) {
+                    let new_board = frame.board.union(&Board(placement_mask));
+                    used_placements[piece_idx] = placement_mask;
+                    let is_promising = judge_connected_component(new_board, unused_piece_sizes(used_placements, piece_sizes));
+                    used_placements[piece_idx] = 0;
+                    if is_promising {
+                        child = Some((piece_idx, placement_mask, new_board));
+                        break;
                     }
-                    if is_valid { placements.insert(board_to_bitmask(&board)); }
+                }
+            }
+
+            match child {
+                // 子ノードへ潜る - 積んでから`on_node`を呼ぶので、ここで中断しても再開時はこの子ノードから続く
+                Some((piece_idx, placement_mask, board)) => {
+                    used_placements[piece_idx] = placement_mask;
+                    stack.push(Frame { board, placed_piece: Some(piece_idx), next_index: 0 });
+                    if !on_node() { return false; }
+                }
+                // この段の配置を試し終えたのでバックトラック
+                None => {
+                    if let Some(piece_idx) = frame.placed_piece { used_placements[piece_idx] = 0; }
+                    stack.pop();
                 }
             }
         }
-        placements.into_iter().collect()
-    }).collect();
+        true
+    }
+}
 
-    // --- 盤面初期化フェーズ ---
-    // 1. まず空の盤面(0)を用意
-    let mut initial_board = vec![vec![0u8; 7]; 7];
-
-    // 2. 常に固定の穴(1)をマーク
-    initial_board[0][6] = 1;
-    initial_board[1][6] = 1;
-    initial_board[6][3] = 1;
-    initial_board[6][4] = 1;
-    initial_board[6][5] = 1;
-    initial_board[6][6] = 1;
-
-    // 3. 指定された月日の穴(1)をマーク
-    let month_r = ((month - 1) / 6) as usize;
-    let month_c = ((month - 1) % 6) as usize;
-    initial_board[month_r][month_c] = 1;
-
-    let day_r = ((day - 1) / 7 + 2) as usize;
-    let day_c = ((day - 1) % 7) as usize;
-    initial_board[day_r][day_c] = 1;
-    
-    // 4. 正しい盤面のビットマスクを生成
-    let initial_board_mask = board_to_bitmask(&initial_board);
-    
-    // --- 探索実行フェーズ ---
-    let mut found_raw_solutions = Vec::new();
-    find_solutions_recursive(0, initial_board_mask, &mut Vec::new(), false, &all_piece_placements, size_6_piece_index, &mut found_raw_solutions);
+/// 盤面`start_board`から全ピースを置く探索を実行する
+/// 通常は高速な再帰版を使い、`iterative-search`フィーチャーを有効にしてビルドした場合は反復版を使う
+fn run_search<F: FnMut(&[u64; 8]) -> bool>(
+    start_board: Board, all_piece_placements: &[Vec<u64>], on_solution: &mut F,
+) -> bool {
+    run_search_from(start_board, [0; 8], all_piece_placements, on_solution)
+}
 
-    // --- 結果の変換・返却フェーズ ---
-    // 探索結果（ビットマスクのリスト）を、JavaScriptが扱いやすい`Solution`構造体のリストに変換する
-    let final_solutions: Vec<Solution> = found_raw_solutions.iter().map(|masks| {
-        let mut board = vec![vec![0i8; 7]; 7];
-        for (piece_id, &mask) in masks.iter().enumerate() {
-            for i in 0..49 {
-                if (mask >> i) & 1 == 1 {
-                    board[i / 7][i % 7] = (piece_id + 1) as i8;
+/// `run_search`の、一部のピースを置いた状態から始める版
+/// `pinned`の要素iはピースiの固定した配置ビットマスク（未配置なら0）で、`start_board`にはそのマスも埋めておく
+fn run_search_from<F: FnMut(&[u64; 8]) -> bool>(
+    start_board: Board, pinned: [u64; 8], all_piece_placements: &[Vec<u64>], on_solution: &mut F,
+) -> bool {
+    run_indexed_search(start_board, pinned, &cell_placement_index(all_piece_placements), on_solution)
+}
+
+/// `run_search_from`の、配置パターンの索引（`cell_placement_index`）を直接受け取る版
+/// 索引の各リストの並び順を変えると、解が見つかる順序だけが変わる
+fn run_indexed_search<F: FnMut(&[u64; 8]) -> bool>(
+    start_board: Board, pinned: [u64; 8], cell_placements: &[Vec<(usize, u64)>], on_solution: &mut F,
+) -> bool {
+    run_interruptible_search(start_board, pinned, cell_placements, on_solution, &mut || true)
+}
+
+/// `run_indexed_search`の、探索木の各ノードで`on_node`を呼び出す版 - `on_node`がfalseを返したら探索を打ち切る
+fn run_interruptible_search<F: FnMut(&[u64; 8]) -> bool, G: FnMut() -> bool>(
+    start_board: Board, pinned: [u64; 8], cell_placements: &[Vec<(usize, u64)>],
+    on_solution: &mut F, on_node: &mut G,
+) -> bool {
+    let mut used_placements = pinned;
+    let piece_sizes = indexed_piece_sizes(cell_placements);
+    if cfg!(feature = "iterative-search") {
+        find_solutions_iterative(start_board, &mut used_placements, cell_placements, &piece_sizes, on_solution, on_node)
+    } else {
+        find_solutions_recursive(start_board, &mut used_placements, cell_placements, &piece_sizes, on_solution, on_node)
+    }
+}
+
+/// 配置パターンの索引から、各ピースのマス数を求める - 配置が1つもないピースは0とする
+fn indexed_piece_sizes(cell_placements: &[Vec<(usize, u64)>]) -> [usize; 8] {
+    let mut piece_sizes = [0; 8];
+    for &(piece_idx, placement_mask) in cell_placements.iter().flatten() { piece_sizes[piece_idx] = placement_mask.count_ones() as usize; }
+    piece_sizes
+}
+
+/// まだ置いていない（配置ビットマスクが0の）ピースのマス数
+fn unused_piece_sizes<'a>(used_placements: &'a [u64; 8], piece_sizes: &'a [usize; 8]) -> impl Iterator<Item = usize> + 'a {
+    (0..8).filter(|&piece_idx| used_placements[piece_idx] == 0).map(|piece_idx| piece_sizes[piece_idx])
+}
+
+// --- 事前計算・盤面初期化 ---
+
+/// 全ピースの全配置パターン（ビットマスク）を返す
+/// 探索を始める前に計算しておくことで、探索中の回転や重複チェックのコストをなくし、大幅に高速化する
+/// 計算は最初の呼び出しで一度だけ行い、以降は`PIECE_PLACEMENTS`のキャッシュを返す
+fn compute_piece_placements() -> &'static [Vec<u64>] {
+    &PIECE_PLACEMENTS
+}
+
+/// 標準のピース集合の全ての向きの形を、`normalize_mask`で左上に寄せたビットマスクの集合 - `judge_single_piece_islands`で使う
+static PIECE_SHAPE_MASKS: std::sync::LazyLock<HashSet<u64>> = std::sync::LazyLock::new(|| {
+    let all_piece_placements = compute_piece_placements();
+    all_piece_placements.iter().flatten().map(|&placement_mask| normalize_mask(placement_mask)).collect()
+});
+
+/// 標準のピース集合の各ピースの向きを、`get_unique_rotations`の順に`normalize_mask`で左上に寄せたビットマスク - `standard_orientations_used`で使う
+/// 要素iのk番目がピースiのk番目の向きで、0番目が基本形になる
+static PIECE_ORIENTATION_MASKS: std::sync::LazyLock<Vec<Vec<u64>>> = std::sync::LazyLock::new(|| {
+    get_initial_pieces().iter()
+        .map(|shape| get_unique_rotations(shape).iter().map(|rotated| normalize_mask(Board::from_grid(rotated).0)).collect())
+        .collect()
+});
+
+/// 標準のピース集合の配置パターンは日付によらず同じなので、最初に使われたときに一度だけ計算して使い回す
+/// 全日付を続けて解く集計では、366回分の回転・反転と配置の列挙がなくなる
+static PIECE_PLACEMENTS: std::sync::LazyLock<Vec<Vec<u64>>> = std::sync::LazyLock::new(|| {
+    get_initial_pieces().iter().map(|p_shape| shape_placements(p_shape)).collect()
+});
+
+/// サイズ6のピースの番号を返す - 無い場合は`None`
+fn find_size_6_piece(pieces: &[Vec<Vec<u8>>]) -> Option<usize> {
+    pieces.iter().position(|shape| shape.iter().flatten().filter(|&&cell| cell == 1).count() == 6)
+}
+
+/// 標準のピース集合を裏返さずに（回転だけで）置く場合の配置パターン - ピースの並びは`PIECE_PLACEMENTS`と同じ
+static SINGLE_SIDED_PIECE_PLACEMENTS: std::sync::LazyLock<Vec<Vec<u64>>> = std::sync::LazyLock::new(|| {
+    get_initial_pieces().iter().map(|p_shape| orientation_placements(unique_orientations(p_shape, false), 7)).collect()
+});
+
+/// 1つのピース形状について、盤面内に収まる全ての配置パターン（回転・反転を含む）をビットマスクで列挙する
+fn shape_placements(p_shape: &[Vec<u8>]) -> Vec<u64> {
+    shape_placements_in_rows(p_shape, 7)
+}
+
+/// `shape_placements`の行数を指定できる版 - 幅7列・高さ`rows`行の盤面（bit (行 * 7 + 列)）内の配置を列挙する
+fn shape_placements_in_rows(p_shape: &[Vec<u8>], rows: usize) -> Vec<u64> {
+    orientation_placements(get_unique_rotations(p_shape), rows)
+}
+
+/// 向きの決まった形状のリスト（`trim_shape`で切り詰めたもの）について、幅7列・高さ`rows`行の盤面内に収まる全ての配置パターンを昇順で列挙する
+fn orientation_placements(unique_shapes: Vec<Vec<Vec<u8>>>, rows: usize) -> Vec<u64> {
+    let mut placements = HashSet::new();
+    for shape in unique_shapes {
+        if shape.is_empty() { continue; } // 埋まったマスの無い形状は`trim_shape`で空になり、どこにも置けない
+        let (h, w) = (shape.len(), shape[0].len());
+        if h > rows || w > 7 { continue; } // 盤面より大きな形状はどこにも置けない
+        for r in 0..(rows + 1 - h) {
+            for c in 0..(8 - w) {
+                // 形状は埋まっているマスの外接長方形に切り詰めてあるので、この範囲の(r, c)なら必ず盤面に収まる
+                let mut board = Board::default();
+                for (i, row) in shape.iter().enumerate() {
+                    for (j, &cell) in row.iter().enumerate() {
+                        if cell == 1 { board.set_bit((r + i) * 7 + c + j); }
+                    }
                 }
+                placements.insert(board.0);
             }
         }
-        // 日付の穴を-1でマーク
-        board[month_r][month_c] = -1;
-        board[day_r][day_c] = -1;
+    }
+    // `HashSet`の列挙順は実行ごとに変わりうるので、探索順序を決定的にするため昇順に並べる
+    let mut placements: Vec<u64> = placements.into_iter().collect();
+    placements.sort_unstable();
+    placements
+}
 
-        Solution { board }
-    }).collect();
+/// 標準の盤面での月の穴の座標 (行, 列)
+fn month_cell(month: u32) -> (usize, usize) { BoardLayout::dragon_fjord().month_cell(month) }
+
+/// 標準の盤面での日の穴の座標 (行, 列)
+fn day_cell(day: u32) -> (usize, usize) { BoardLayout::dragon_fjord().day_cell(day) }
+
+/// 標準の盤面で、固定の穴と指定された月日の穴を埋めた、探索開始時の盤面を作る
+fn initial_board(month: u32, day: u32) -> Board { BoardLayout::dragon_fjord().initial_board(month, day) }
+
+/// 探索結果（ピースごとの配置ビットマスク）を、JavaScriptが扱いやすい`Solution`構造体に変換する
+fn masks_to_solution(masks: &[u64], month: u32, day: u32) -> Solution {
+    masks_to_solution_for_layout(masks, &BoardLayout::dragon_fjord(), month, day)
+}
+
+/// `masks_to_solution`のレイアウトを指定できる版 - 日付の穴の位置を`layout`から求める
+fn masks_to_solution_for_layout(masks: &[u64], layout: &BoardLayout, month: u32, day: u32) -> Solution {
+    let mut board = vec![vec![0i8; 7]; 7];
+    for (piece_id, &mask) in masks.iter().enumerate() {
+        for i in 0..49 {
+            if (mask >> i) & 1 == 1 {
+                board[i / 7][i % 7] = (piece_id + 1) as i8;
+            }
+        }
+    }
+    // 日付の穴を-1でマーク
+    let (month_r, month_c) = layout.month_cell(month);
+    board[month_r][month_c] = -1;
+    let (day_r, day_c) = layout.day_cell(day);
+    board[day_r][day_c] = -1;
+
+    Solution { board, orientations_used: standard_orientations_used(masks) }
+}
+
+/// 指定された月日の全解をビットマスクのリストとして探索する
+fn find_raw_solutions(month: u32, day: u32, all_piece_placements: &[Vec<u64>]) -> Vec<[u64; 8]> {
+    find_raw_solutions_for_layout(&BoardLayout::default(), month, day, all_piece_placements)
+}
+
+/// `find_raw_solutions`のレイアウトを指定できる版 - 全解を集める探索はすべてこの関数を通す
+fn find_raw_solutions_for_layout(layout: &BoardLayout, month: u32, day: u32, all_piece_placements: &[Vec<u64>]) -> Vec<[u64; 8]> {
+    let mut found_raw_solutions = Vec::new();
+    run_search(layout.initial_board(month, day), all_piece_placements, &mut |masks: &[u64; 8]| {
+        found_raw_solutions.push(*masks);
+        true
+    });
+    found_raw_solutions
+}
+
+/// 指定された月日の解の個数だけを数える - 解ごとの配列の複製や盤面の復元を行わないので、個数だけが必要な集計に向く
+fn count_raw_solutions(month: u32, day: u32, all_piece_placements: &[Vec<u64>]) -> u32 {
+    let mut count = 0;
+    run_search(initial_board(month, day), all_piece_placements, &mut |_: &[u64; 8]| {
+        count += 1;
+        true
+    });
+    count
+}
+
+/// 指定された月日の全解を`Solution`のリストとして求める
+fn solutions_for_date(month: u32, day: u32) -> Vec<Solution> {
+    solutions_for_layout(&BoardLayout::default(), month, day)
+}
+
+/// 盤面のレイアウト`layout`で、指定された月日の全解を`Solution`のリストとして求める
+fn solutions_for_layout(layout: &BoardLayout, month: u32, day: u32) -> Vec<Solution> {
+    // --- 事前計算フェーズ ---
+    let all_piece_placements = compute_piece_placements();
+
+    // --- 探索実行フェーズ ---
+    let found_raw_solutions = find_raw_solutions_for_layout(layout, month, day, all_piece_placements);
+
+    // --- 結果の変換フェーズ ---
+    found_raw_solutions.iter().map(|masks| masks_to_solution_for_layout(masks, layout, month, day)).collect()
+}
+
+/// 盤面のレイアウト名`layout_name`（`BoardLayout::from_name`が受け付ける名前で、今は"dragon_fjord"のみ）を指定して、指定された月日の全解を求める
+/// 月や日の並びが異なる盤面を使っている場合に、正しい位置に日付の穴を開けて解くためのもの
+#[wasm_bindgen]
+pub fn solve_for_date_layout(month: u32, day: u32, layout_name: &str) -> Result<JsValue, JsValue> {
+    console_error_panic_hook::set_once();
+    validate_date(month, day)?;
+
+    let layout = BoardLayout::from_name(layout_name)
+        .ok_or_else(|| JsValue::from_str(&format!("未対応の盤面レイアウトです: {}", layout_name)))?;
+    Ok(serde_wasm_bindgen::to_value(&solutions_for_layout(&layout, month, day))?)
+}
+
+/// 配置パターン`all_piece_placements`で、指定された月日の解を探索順（決定的な順序）で先頭からn個まで求める - n個集まった時点で探索を打ち切る
+fn first_n_raw_solutions(month: u32, day: u32, n: usize, all_piece_placements: &[Vec<u64>]) -> Vec<[u64; 8]> {
+    let mut found_raw_solutions = Vec::new();
+    if n == 0 { return found_raw_solutions; }
+    run_search(initial_board(month, day), all_piece_placements, &mut |masks: &[u64; 8]| {
+        found_raw_solutions.push(*masks);
+        found_raw_solutions.len() < n
+    });
+    found_raw_solutions
+}
+
+/// 指定された月日の全解を、ピース1〜8の配置ビットマスクの配列のリストで返す（ネイティブ環境向け）
+/// `JsValue`を使わない純粋なRustの入口で、ベンチマークやテスト、コマンドラインから使う
+pub fn solve_raw(month: u32, day: u32) -> Result<Vec<[u64; 8]>, String> {
+    if !is_valid_date(month, day) { return Err(invalid_date_message(month, day)); }
+
+    Ok(find_raw_solutions(month, day, compute_piece_placements()))
+}
+
+/// 指定された月日の解を、見つかるたびに`on_solution`へ1つずつ渡し、最後に解の個数を返す（ネイティブ環境向け）
+/// 全解を集めてから返さないので、解が多い日付でもメモリを使わずに逐次処理できる
+pub fn solve_streaming<F: FnMut(&[u64; 8])>(month: u32, day: u32, mut on_solution: F) -> Result<u32, String> {
+    if !is_valid_date(month, day) { return Err(invalid_date_message(month, day)); }
+
+    let all_piece_placements = compute_piece_placements();
+    let mut count = 0;
+    run_search(initial_board(month, day), all_piece_placements, &mut |masks: &[u64; 8]| {
+        on_solution(masks);
+        count += 1;
+        true
+    });
+    Ok(count)
+}
+
+/// 指定された月日の全解を`Solution`のリストで返す（ネイティブ環境向け） - 内容は`solve_for_date`と同じ
+pub fn solve(month: u32, day: u32) -> Result<Vec<Solution>, String> {
+    Ok(solve_raw(month, day)?.iter().map(|masks| masks_to_solution(masks, month, day)).collect())
+}
+
+/// 指定された月日の解を、見つかった順（探索順）に1つずつチャネルへ送る（ネイティブ環境向け）
+/// 探索は呼び出したスレッドで行うので、受信側と並行に処理したい場合は`std::thread::spawn`の中で呼ぶ
+/// 受信側が破棄されて送信に失敗した時点で探索を打ち切る
+/// 存在しない日付を指定した場合は、何も送らずに`solve_raw`と同じエラーメッセージを返す
+#[cfg(not(target_arch = "wasm32"))]
+pub fn solve_to_channel(month: u32, day: u32, tx: std::sync::mpsc::Sender<Solution>) -> Result<(), String> {
+    if !is_valid_date(month, day) { return Err(invalid_date_message(month, day)); }
+
+    let all_piece_placements = compute_piece_placements();
+    run_search(initial_board(month, day), all_piece_placements, &mut |masks: &[u64; 8]| {
+        tx.send(masks_to_solution(masks, month, day)).is_ok()
+    });
+    Ok(())
+}
+
+/// WASMとしてJavaScriptに公開されるメイン関数
+/// `#[wasm_bindgen]` アトリビュートにより、このRust関数がJavaScriptから直接呼び出せるようになる
+/// 存在しない日付（13月や4月31日など）を指定すると、`{"error":"invalid_date","month":..,"day":..}`形式のエラーを返す
+///
+/// 解の並び順は実行・ビルドによらず常に同じで、次の全順序に従う:
+/// 各解を「番号（行 * 7 + 列）の小さい空きマスから順に、そのマスを覆うピースの (ピース番号, 配置ビットマスク)」の列とみなし、
+/// その列を辞書順に比較した昇順（探索が配置を試す順序そのもの）
+#[wasm_bindgen]
+pub fn solve_for_date(month: u32, day: u32) -> Result<JsValue, JsValue> {
+    // Rustがパニックした際に、ブラウザのコンソールにエラーを出力する設定
+    console_error_panic_hook::set_once();
+    // 範囲外の月日は盤面の外や別のマスを指してしまうので、探索の前に弾く
+    validate_date(month, day)?;
+
+    let final_solutions = solutions_for_date(month, day);
 
     // `serde_wasm_bindgen`を使って、Rustの`Vec<Solution>`をJavaScriptの`JsValue`にシリアライズして返す
     // `?`はシリアライズ中にエラーが発生した場合に、そのエラーをJavaScript側に送るための糖衣構文
     Ok(serde_wasm_bindgen::to_value(&final_solutions)?)
-}
\ No newline at end of file
+}
+
+/// "3-15"・"03-15"・"2024-03-15"（年は無視する）の形式の文字列から (月, 日) を読み取る
+/// 形式が不正な場合と、存在しない日付の場合はエラーメッセージを返す
+fn parse_date_str(date: &str) -> Result<(u32, u32), String> {
+    let parts: Vec<&str> = date.split('-').collect();
+    let is_number = |part: &&str| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit());
+    let (month, day) = match parts.as_slice() {
+        [month, day] | [_, month, day] if parts.iter().all(is_number) => (month, day),
+        _ => return Err(format!("日付は\"月-日\"か\"年-月-日\"の形式で指定してください: \"{}\"", date)),
+    };
+    let (Ok(month), Ok(day)) = (month.parse(), day.parse()) else {
+        return Err(format!("日付の数値が大きすぎます: \"{}\"", date));
+    };
+    if !is_valid_date(month, day) { return Err(invalid_date_message(month, day)); }
+    Ok((month, day))
+}
+
+/// "3-15"・"03-15"・"2024-03-15"（年は無視する）の形式の日付文字列を受け取り、`solve_for_date`と同じ全解を返す
+/// JavaScriptの日付文字列をそのまま渡せるようにするための入口
+#[wasm_bindgen]
+pub fn solve_for_date_str(date: &str) -> Result<JsValue, JsValue> {
+    console_error_panic_hook::set_once();
+
+    let (month, day) = parse_date_str(date).map_err(|e| JsValue::from_str(&e))?;
+    Ok(serde_wasm_bindgen::to_value(&solutions_for_date(month, day))?)
+}
+
+/// 指定された月日の解のうち、`solve_for_date`が返す順序で先頭のn個だけを返す
+/// 解の順序は決定的なので、同じnなら常に同じ解が返り、`solve_for_date`の結果の先頭n個と一致する
+/// n個見つかった時点で探索を打ち切るため、全解を列挙するよりも速い
+#[wasm_bindgen]
+pub fn first_n_solutions(month: u32, day: u32, n: usize) -> Result<JsValue, JsValue> {
+    console_error_panic_hook::set_once();
+    validate_date(month, day)?;
+
+    let solutions: Vec<Solution> = first_n_raw_solutions(month, day, n, compute_piece_placements()).iter().map(|masks| masks_to_solution(masks, month, day)).collect();
+    Ok(serde_wasm_bindgen::to_value(&solutions)?)
+}
+
+/// ピースを裏返さずに（回転だけで）置く場合の、指定された月日の全解を返す
+/// 表裏が色分けされていて裏返せないピースのパズル向けで、結果は必ず`solve_for_date`の結果の一部になる
+/// 裏返しが使えない分だけ解は減り、解が1つもなくなる日付もある
+#[wasm_bindgen]
+pub fn solve_for_date_no_flip(month: u32, day: u32) -> Result<JsValue, JsValue> {
+    console_error_panic_hook::set_once();
+    validate_date(month, day)?;
+
+    let solutions: Vec<Solution> = find_raw_solutions(month, day, &SINGLE_SIDED_PIECE_PLACEMENTS)
+        .iter().map(|masks| masks_to_solution(masks, month, day)).collect();
+    Ok(serde_wasm_bindgen::to_value(&solutions)?)
+}
+
+/// 指定された月日の解を、探索順で最大`max_solutions`個まで返す - `max_solutions`が0なら上限なしで全解を返す
+/// 上限に達した時点で探索そのものを打ち切るので、1個や5個に絞ると全解の列挙よりずっと速い
+/// 結果は常に`solve_for_date`の結果の先頭部分と一致する
+#[wasm_bindgen]
+pub fn solve_for_date_limited(month: u32, day: u32, max_solutions: u32) -> Result<JsValue, JsValue> {
+    console_error_panic_hook::set_once();
+    validate_date(month, day)?;
+
+    Ok(serde_wasm_bindgen::to_value(&limited_solutions(month, day, max_solutions))?)
+}
+
+/// 指定された月日の解を、探索順で最大`max_solutions`個（0なら上限なし）まで求める
+fn limited_solutions(month: u32, day: u32, max_solutions: u32) -> Vec<Solution> {
+    if max_solutions == 0 { return solutions_for_date(month, day); }
+    first_n_raw_solutions(month, day, max_solutions as usize, compute_piece_placements()).iter().map(|masks| masks_to_solution(masks, month, day)).collect()
+}
+
+/// 時間制限付きの探索の結果
+#[derive(Serialize)]
+struct TimedSolutions {
+    /// 制限時間内に見つかった解（探索順）
+    solutions: Vec<Solution>,
+    /// 探索を最後まで終えたかどうか - falseなら`solutions`は全解の先頭部分
+    complete: bool,
+}
+
+/// 経過時間を測るための現在時刻（ミリ秒） - WASMでは`Date.now()`、ネイティブではシステム時計を使う
+fn now_ms() -> f64 {
+    #[cfg(target_arch = "wasm32")]
+    { js_sys::Date::now() }
+    #[cfg(not(target_arch = "wasm32"))]
+    { std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map_or(0.0, |d| d.as_secs_f64() * 1000.0) }
+}
+
+/// 時刻を確認する間隔（探索木のノード数） - ノードごとに時計を読むと遅くなるため間引く
+const TIME_CHECK_INTERVAL: u32 = 1024;
+
+/// 指定された月日の解を、`budget_ms`ミリ秒を超えるまで探索順に求める
+fn solutions_within_budget(month: u32, day: u32, budget_ms: f64) -> TimedSolutions {
+    let start_ms = now_ms();
+    let all_piece_placements = compute_piece_placements();
+    let mut found_raw_solutions = Vec::new();
+    let mut nodes = 0u32;
+    let complete = run_interruptible_search(
+        initial_board(month, day), [0; 8], &cell_placement_index(all_piece_placements),
+        &mut |masks: &[u64; 8]| { found_raw_solutions.push(*masks); true },
+        &mut || {
+            nodes = nodes.wrapping_add(1);
+            nodes % TIME_CHECK_INTERVAL != 1 || now_ms() - start_ms < budget_ms
+        },
+    );
+    let solutions = found_raw_solutions.iter().map(|masks| masks_to_solution(masks, month, day)).collect();
+    TimedSolutions { solutions, complete }
+}
+
+/// 指定された月日の解を、`budget_ms`ミリ秒の制限時間内で求めて`{ solutions, complete }`を返す
+/// 制限時間を超えた時点で探索を打ち切り、それまでに見つかった解と`complete: false`を返すので、UIスレッドを長く止めずに済む
+/// 時刻は一定のノード数ごとにしか確認しないため、実際の処理時間は制限時間をわずかに超えることがある
+#[wasm_bindgen]
+pub fn solve_for_date_timed(month: u32, day: u32, budget_ms: f64) -> Result<JsValue, JsValue> {
+    console_error_panic_hook::set_once();
+    validate_date(month, day)?;
+
+    Ok(serde_wasm_bindgen::to_value(&solutions_within_budget(month, day, budget_ms))?)
+}
+
+/// `SolverSession::step`の結果
+#[derive(Serialize)]
+struct StepResult {
+    /// この呼び出しで新たに見つかった解（探索順）
+    solutions: Vec<Solution>,
+    /// 探索を最後まで終えたかどうか
+    done: bool,
+}
+
+/// 探索を少しずつ進めるためのセッション - Web Workerやアニメーションフレームごとに`step`を呼び、UIを止めずに全解を求める
+/// `step`を繰り返して見つかる解の集合と順序は、`solve_for_date`と同じ
+#[wasm_bindgen]
+pub struct SolverSession {
+    month: u32,
+    day: u32,
+    cell_placements: Vec<Vec<(usize, u64)>>,
+    piece_sizes: [usize; 8],
+    search: IterativeSearch,
+    /// これまでに見つかった全ての解（探索順）
+    collected: Vec<[u64; 8]>,
+}
+
+impl SolverSession {
+    /// 探索を最大`max_nodes`ノード（最低1ノード）進め、新たに見つかった解を返す
+    fn advance(&mut self, max_nodes: u32) -> Vec<[u64; 8]> {
+        let already_found = self.collected.len();
+        let collected = &mut self.collected;
+        let mut nodes = 0u32;
+        self.search.resume(
+            &self.cell_placements, &self.piece_sizes,
+            &mut |masks: &[u64; 8]| { collected.push(*masks); true },
+            &mut || { nodes += 1; nodes < max_nodes },
+        );
+        self.collected[already_found..].to_vec()
+    }
+}
+
+#[wasm_bindgen]
+impl SolverSession {
+    /// 指定された月日の探索を始める準備をする - この時点ではまだ探索しない
+    #[wasm_bindgen(constructor)]
+    pub fn new(month: u32, day: u32) -> Result<SolverSession, JsValue> {
+        console_error_panic_hook::set_once();
+        validate_date(month, day)?;
+
+        let cell_placements = cell_placement_index(compute_piece_placements());
+        let piece_sizes = indexed_piece_sizes(&cell_placements);
+        let search = IterativeSearch::new(initial_board(month, day), [0; 8]);
+        Ok(SolverSession { month, day, cell_placements, piece_sizes, search, collected: Vec::new() })
+    }
+
+    /// 探索を最大`max_nodes`ノード（探索木のノード数、0でも1ノードは進む）進めて`{ solutions, done }`を返す
+    /// `solutions`はこの呼び出しで新たに見つかった解だけで、`done`がtrueになった後の呼び出しは空の配列を返す
+    pub fn step(&mut self, max_nodes: u32) -> Result<JsValue, JsValue> {
+        let solutions = self.advance(max_nodes).iter().map(|masks| masks_to_solution(masks, self.month, self.day)).collect();
+        Ok(serde_wasm_bindgen::to_value(&StepResult { solutions, done: self.search.is_finished() })?)
+    }
+
+    /// これまでの`step`で見つかった全ての解を、探索順に返す
+    pub fn collected(&self) -> Result<JsValue, JsValue> {
+        let solutions: Vec<Solution> = self.collected.iter().map(|masks| masks_to_solution(masks, self.month, self.day)).collect();
+        Ok(serde_wasm_bindgen::to_value(&solutions)?)
+    }
+}
+
+/// 指定された月日の解を1つだけ返す - 「答えを1つ見せる」ボタン向けの最速の経路で、最初の解が見つかった時点で探索を打ち切る
+/// 解が存在しない日付では、空の配列ではなく`null`を返す（標準の盤面とピース集合では全ての日付に解があり、裏返せないピースの1月14日などが解の無い例になる）
+#[wasm_bindgen]
+pub fn solve_first_for_date(month: u32, day: u32) -> Result<JsValue, JsValue> {
+    console_error_panic_hook::set_once();
+    validate_date(month, day)?;
+
+    match first_solution(month, day, compute_piece_placements()) {
+        Some(solution) => Ok(serde_wasm_bindgen::to_value(&solution)?),
+        None => Ok(JsValue::NULL),
+    }
+}
+
+/// 配置パターン`all_piece_placements`で、指定された月日の探索順で最初の解を求める（解が無い日付では`None`）
+fn first_solution(month: u32, day: u32, all_piece_placements: &[Vec<u64>]) -> Option<Solution> {
+    first_n_raw_solutions(month, day, 1, all_piece_placements).first().map(|masks| masks_to_solution(masks, month, day))
+}
+
+/// 指定された月日の解を、見つかるたびに`callback`へ盤面（`Solution`の`board`と同じ形式）を渡して呼び出し、最後に解の個数を返す
+/// 全解を1つの配列にまとめないので、解の多い日付でもメモリを抑えつつ、最初の解からすぐに描画を始められる
+/// `callback`が例外を投げた場合は、その時点で探索を打ち切ってエラーとして返す
+#[wasm_bindgen]
+pub fn solve_for_date_streaming(month: u32, day: u32, callback: &js_sys::Function) -> Result<u32, JsValue> {
+    console_error_panic_hook::set_once();
+    validate_date(month, day)?;
+
+    let all_piece_placements = compute_piece_placements();
+    let mut count = 0;
+    let mut callback_error = None;
+    run_search(initial_board(month, day), all_piece_placements, &mut |masks: &[u64; 8]| {
+        let result = serde_wasm_bindgen::to_value(&masks_to_solution(masks, month, day).board)
+            .map_err(JsValue::from)
+            .and_then(|board| callback.call1(&JsValue::NULL, &board));
+        match result {
+            Ok(_) => { count += 1; true }
+            Err(e) => { callback_error = Some(e); false }
+        }
+    });
+    match callback_error {
+        Some(e) => Err(e),
+        None => Ok(count),
+    }
+}
+
+/// 解の中の1つのピース - JavaScript側でピースごとに描画やアニメーションをするために使う
+#[derive(Serialize)]
+struct PlacedPiece {
+    /// ピースの番号（1〜8、`Solution`の`board`の値と同じ）
+    id: u8,
+    /// ピースが覆っているマスの座標 (行, 列) - 行優先の昇順
+    cells: Vec<(u8, u8)>,
+}
+
+/// ピースごとのマスのリストで表した解
+#[derive(Serialize)]
+struct SolutionDetailed {
+    pieces: Vec<PlacedPiece>,
+    /// 日付の2つの穴の座標 (行, 列) - `Solution`の`board`で-1になるマス
+    holes: Vec<(u8, u8)>,
+}
+
+/// ビットマスクのマスを、行優先の昇順で座標 (行, 列) のリストにする
+fn mask_to_cells(mask: u64) -> Vec<(u8, u8)> {
+    (0..49).filter(|&bit| (mask >> bit) & 1 == 1).map(|bit| ((bit / 7) as u8, (bit % 7) as u8)).collect()
+}
+
+/// 探索結果（ピースごとの配置ビットマスク）を、ピースごとのマスのリストで表した解に変換する
+fn masks_to_detailed(masks: &[u64], month: u32, day: u32) -> SolutionDetailed {
+    let pieces = masks.iter().enumerate()
+        .map(|(piece_idx, &mask)| PlacedPiece { id: (piece_idx + 1) as u8, cells: mask_to_cells(mask) })
+        .collect();
+    let (month_r, month_c) = month_cell(month);
+    let (day_r, day_c) = day_cell(day);
+    SolutionDetailed { pieces, holes: vec![(month_r as u8, month_c as u8), (day_r as u8, day_c as u8)] }
+}
+
+/// 指定された月日の全解を、ピースごとの番号と覆っているマスのリスト`{ pieces: [{ id, cells }], holes }`で返す
+/// マスは配置ビットマスクから直接求めるので、`board`から読み戻すよりも、どのマスがどのピースのものかが明確になる
+/// 解の順序は`solve_for_date`と同じ
+#[wasm_bindgen]
+pub fn solve_for_date_detailed(month: u32, day: u32) -> Result<JsValue, JsValue> {
+    console_error_panic_hook::set_once();
+    validate_date(month, day)?;
+
+    let all_piece_placements = compute_piece_placements();
+    let solutions: Vec<SolutionDetailed> = find_raw_solutions(month, day, all_piece_placements)
+        .iter().map(|masks| masks_to_detailed(masks, month, day)).collect();
+    Ok(serde_wasm_bindgen::to_value(&solutions)?)
+}
+
+/// 指定された月日の解の個数だけを返す - 盤面を組み立てないので`solve_for_date`より軽く、難易度のヒートマップ作成などに使う
+/// 結果は`solve_for_date`が返す配列の長さと一致する
+#[wasm_bindgen]
+pub fn count_solutions_for_date(month: u32, day: u32) -> Result<u32, JsValue> {
+    console_error_panic_hook::set_once();
+    validate_date(month, day)?;
+
+    let all_piece_placements = compute_piece_placements();
+    Ok(count_raw_solutions(month, day, all_piece_placements))
+}
+
+/// 指定された月日に解が1つ以上あるかを返す - `solve_first_for_date`と同じく最初の解が見つかった時点で探索を打ち切る
+/// 存在しない日付は`Err`になるので、「解なし」（`Ok(false)`）と入力の誤りを区別できる
+/// 標準のピース集合では全ての日付で`true`になる
+#[wasm_bindgen]
+pub fn is_date_solvable(month: u32, day: u32) -> Result<bool, JsValue> {
+    console_error_panic_hook::set_once();
+    validate_date(month, day)?;
+
+    Ok(!first_n_raw_solutions(month, day, 1, compute_piece_placements()).is_empty())
+}
+
+/// 1つの解をビットマスクのまま表したもの - bit (行 * 7 + 列) がそのマスを表す
+#[derive(Serialize)]
+struct BitmaskSolution {
+    /// 要素iはピースi+1の配置ビットマスク
+    pieces: [u64; 8],
+    /// 固定の穴と日付の2マスのビットマスク
+    holes: u64,
+}
+
+/// 指定された月日の全解を、ピースごとの配置ビットマスクと穴のビットマスクで返す - 盤面の配列に戻さずにビット演算したい利用者向け
+/// `u64`の値はJavaScript側では`BigInt`として受け取る（8個の`pieces`と`holes`のORは`0x1FFFFFFFFFFFFn`になる）
+#[wasm_bindgen]
+pub fn solve_for_date_bitmasks(month: u32, day: u32) -> Result<JsValue, JsValue> {
+    console_error_panic_hook::set_once();
+    validate_date(month, day)?;
+
+    let serializer = serde_wasm_bindgen::Serializer::new().serialize_large_number_types_as_bigints(true);
+    Ok(bitmask_solutions(month, day).serialize(&serializer)?)
+}
+
+/// 指定された月日の全解を、配置ビットマスクと穴のビットマスクの組にする
+fn bitmask_solutions(month: u32, day: u32) -> Vec<BitmaskSolution> {
+    let all_piece_placements = compute_piece_placements();
+    let holes = initial_board(month, day).0;
+    find_raw_solutions(month, day, all_piece_placements)
+        .into_iter().map(|pieces| BitmaskSolution { pieces, holes }).collect()
+}
+
+// --- 解の再生（リプレイ） ---
+
+/// 解を探索と同じ順に1手ずつ再生したときの1ステップ
+#[derive(Serialize)]
+struct ReplayStep {
+    /// このステップで置いたピースのID(1-8)
+    piece_id: i8,
+    /// ピースが占めるマスの座標 (行, 列) のリスト
+    cells: Vec<(u8, u8)>,
+    /// このピースを置いた直後の盤面 - まだ置いていないピースのマスは0
+    board: Vec<Vec<i8>>,
+    /// 穴や置き済みのピースと重ならずに置けたかどうか
+    no_overlap: bool,
+}
+
+/// 解の盤面から、探索開始時に埋まっていたマス（固定の穴と日付の穴(-1)）の盤面を作る
+fn solution_hole_board(solution: &Solution) -> Board {
+    let mut holes = Board::default();
+    for &(r, c) in &FIXED_HOLES { holes.set(r, c); }
+    for (r, row) in solution.board.iter().enumerate() {
+        for (c, &cell) in row.iter().enumerate() {
+            if cell == -1 { holes.set(r, c); }
+        }
+    }
+    holes
+}
+
+/// 解の盤面から、ピースID 1〜8 それぞれが占めるマスのビットマスクを取り出す
+fn solution_piece_masks(solution: &Solution) -> Vec<u64> {
+    (1..=8).map(|piece_id| {
+        let mut piece = Board::default();
+        for (r, row) in solution.board.iter().enumerate() {
+            for (c, &cell) in row.iter().enumerate() {
+                if cell == piece_id { piece.set(r, c); }
+            }
+        }
+        piece.0
+    }).collect()
+}
+
+/// 解の盤面を探索と同じ順序で再生し、各ステップで重なりが無いことを検証する
+/// 探索は最も番号の小さい空きマスを埋めるピースから置いていくので、ピースの最も番号の小さいマス（bit (行 * 7 + 列)）の昇順に置く
+/// 固定の穴と日付の穴(-1)を初期状態の「埋まっているマス」として扱い、盤面に無いピースは最後に回す
+fn replay_solution(solution: &Solution) -> Vec<ReplayStep> {
+    let mut occupied = solution_hole_board(solution);
+    let piece_masks = solution_piece_masks(solution);
+    let mut piece_order: Vec<usize> = (0..8).collect();
+    piece_order.sort_by_key(|&piece_idx| piece_masks[piece_idx].trailing_zeros());
+    let mut placed_ids = Vec::new();
+    piece_order.into_iter().map(|piece_idx| {
+        let (piece_mask, piece_id) = (piece_masks[piece_idx], piece_idx as i8 + 1);
+        let piece = Board(piece_mask);
+        let cells = (0..49).filter(|&i| (piece_mask >> i) & 1 == 1).map(|i| ((i / 7) as u8, (i % 7) as u8)).collect();
+        let no_overlap = piece != Board::default() && !occupied.overlaps(&piece);
+        occupied = occupied.union(&piece);
+        placed_ids.push(piece_id);
+
+        // まだ置いていないピースのマスを空き(0)に戻した盤面を作る
+        let board = solution.board.iter().map(|row| {
+            row.iter().map(|&cell| if cell > 0 && !placed_ids.contains(&cell) { 0 } else { cell }).collect()
+        }).collect();
+
+        ReplayStep { piece_id, cells, board, no_overlap }
+    }).collect()
+}
+
+/// `solve_for_date`が返した解を受け取り、ピースを置く手順を探索と同じ順に1手ずつ返す
+/// チュートリアルやアニメーション表示と、返された解の整合性チェックに使う
+#[wasm_bindgen]
+pub fn replay(solution: JsValue) -> Result<JsValue, JsValue> {
+    console_error_panic_hook::set_once();
+
+    let solution: Solution = serde_wasm_bindgen::from_value(solution)?;
+    if solution.board.len() != 7 || solution.board.iter().any(|row| row.len() != 7) {
+        return Err(JsValue::from_str("盤面は7x7である必要があります"));
+    }
+    Ok(serde_wasm_bindgen::to_value(&replay_solution(&solution))?)
+}
+
+
+// --- 解の検証 ---
+
+/// 盤面が指定された月日の正しい解になっているかを調べる
+/// 日付の2マスだけが-1、固定の穴だけが0で、ピース1〜8がそれぞれ自分の形（回転・反転を含む）の通りにちょうど1回ずつ置かれていれば正しい
+/// ピースの形は探索と同じ`compute_piece_placements`の配置パターンと照らし合わせるので、探索が返せない並べ方を正しいと判定することはない
+fn is_valid_solution_board(month: u32, day: u32, board: &[Vec<i8>]) -> bool {
+    if board.len() != 7 || board.iter().any(|row| row.len() != 7) { return false; }
+
+    let date_cells = [month_cell(month), day_cell(day)];
+    for (r, row) in board.iter().enumerate() {
+        for (c, &cell) in row.iter().enumerate() {
+            let expected_hole = if date_cells.contains(&(r, c)) { Some(-1) } else if FIXED_HOLES.contains(&(r, c)) { Some(0) } else { None };
+            match expected_hole {
+                Some(hole) => if cell != hole { return false; },
+                None => if !(1..=8).contains(&cell) { return false; },
+            }
+        }
+    }
+
+    // 穴以外の全マスがいずれかのピースで覆われているので、あとは各ピースのマスがその形の配置になっているかを見ればよい
+    let solution = Solution { board: board.to_vec(), orientations_used: 0 };
+    solution_piece_masks(&solution).iter().zip(compute_piece_placements())
+        .all(|(piece_mask, placements)| placements.binary_search(piece_mask).is_ok())
+}
+
+/// ユーザーが手で並べた盤面（`Solution`の`board`と同じ形式）が、指定された月日の正しい解かどうかを返す
+/// 日付の穴・固定の穴の位置、全マスがちょうど1回ずつ覆われていること、各ピースの形が正しいことを確かめる
+#[wasm_bindgen]
+pub fn verify_solution(month: u32, day: u32, board: JsValue) -> Result<bool, JsValue> {
+    console_error_panic_hook::set_once();
+    validate_date(month, day)?;
+
+    let board: Vec<Vec<i8>> = serde_wasm_bindgen::from_value(board)?;
+    Ok(is_valid_solution_board(month, day, &board))
+}
+
+// --- カレンダー全体の集計 ---
+
+/// ある日付と、その日付の解の個数
+#[derive(Serialize, Clone, Copy)]
+struct DateCount {
+    month: u32,
+    day: u32,
+    count: u32,
+}
+
+/// 各月の日数 - うるう年も扱えるよう2月は29日までとする
+fn days_in_month(month: u32) -> u32 {
+    match month { 2 => 29, 4 | 6 | 9 | 11 => 30, _ => 31 }
+}
+
+/// カレンダー上の全ての日付 (月, 日) を1月1日から順に列挙する（366日分）
+fn all_dates() -> impl Iterator<Item = (u32, u32)> {
+    (1..=12).flat_map(|month| (1..=days_in_month(month)).map(move |day| (month, day)))
+}
+
+/// 月日がカレンダー上に存在する日付かどうか
+fn is_valid_date(month: u32, day: u32) -> bool {
+    (1..=12).contains(&month) && (1..=days_in_month(month)).contains(&day)
+}
+
+/// 存在しない日付を指定されたときのエラーメッセージ - JavaScript側で`JSON.parse`できるJSON文字列
+/// 例: `{"error":"invalid_date","month":13,"day":1}`
+fn invalid_date_message(month: u32, day: u32) -> String {
+    format!(r#"{{"error":"invalid_date","month":{},"day":{}}}"#, month, day)
+}
+
+/// 月日が1〜12月・その月の日数の範囲に収まっているか確かめる - 範囲外ならJSON形式のエラーを返す
+/// 2月は閏年を考慮して29日まで受け付ける
+fn validate_date(month: u32, day: u32) -> Result<(), JsValue> {
+    if is_valid_date(month, day) { Ok(()) } else { Err(JsValue::from_str(&invalid_date_message(month, day))) }
+}
+
+/// 全ての日付について解の個数を数える - 配置パターンの事前計算は1回だけ行い、全日付で共有する
+fn count_solutions_all_dates() -> Vec<DateCount> {
+    let all_piece_placements = compute_piece_placements();
+    all_dates().map(|(month, day)| {
+        let count = count_raw_solutions(month, day, all_piece_placements);
+        DateCount { month, day, count }
+    }).collect()
+}
+
+/// 解の個数が最小の日付（最難）と最大の日付（最易）を (最難, 最易) の組で返す
+/// 個数が同じ場合は、カレンダー上で早い日付を採用する
+fn solution_count_extremes(counts: &[DateCount]) -> Option<(DateCount, DateCount)> {
+    let hardest = counts.iter().min_by_key(|c| (c.count, c.month, c.day))?;
+    let easiest = counts.iter().max_by_key(|c| (c.count, std::cmp::Reverse((c.month, c.day))))?;
+    Some((*hardest, *easiest))
+}
+
+/// 盤面のバリエーション名を検証する - カレンダー全体の集計やSVG描画は"dragon_fjord"のみ対応
+fn check_variant(variant: &str) -> Result<(), JsValue> {
+    check_variant_name(variant).map_err(|e| JsValue::from_str(&e))
+}
+
+/// `check_variant`の本体 - 名前は`BoardLayout::from_name`で引き、標準以外のレイアウトが追加された場合は
+/// 日付ごとの`solve_for_date_layout`でだけ使えることをエラーメッセージで伝える
+fn check_variant_name(variant: &str) -> Result<(), String> {
+    match BoardLayout::from_name(variant) {
+        Some(layout) if layout == BoardLayout::dragon_fjord() => Ok(()),
+        Some(_) => Err(format!("盤面レイアウト{}はsolve_for_date_layoutでだけ使えます - この関数は\"dragon_fjord\"のみ対応しています", variant)),
+        None => Err(format!("未対応のバリエーションです: {}", variant)),
+    }
+}
+
+/// カレンダー上の全366日について、解の個数を `{ month, day, count }` のリストで1月1日から順に返す
+/// 1年分の難易度ヒートマップを1回の呼び出しで描くためのもので、配置パターンのキャッシュを全日付で共有し、盤面は組み立てない
+#[wasm_bindgen]
+pub fn count_all_dates() -> Result<JsValue, JsValue> {
+    console_error_panic_hook::set_once();
+
+    Ok(serde_wasm_bindgen::to_value(&count_solutions_all_dates())?)
+}
+
+/// `start`から`end`まで（両端を含む）の各日付について解の個数を数える - 年をまたぐ範囲は扱わない
+/// `end`が`start`より前の場合はエラーメッセージを返す
+fn count_solutions_in_range(start: (u32, u32), end: (u32, u32)) -> Result<Vec<DateCount>, String> {
+    if end < start {
+        return Err(format!("終了日({}月{}日)が開始日({}月{}日)より前です", end.0, end.1, start.0, start.1));
+    }
+    let all_piece_placements = compute_piece_placements();
+    Ok(all_dates()
+        .filter(|&date| start <= date && date <= end)
+        .map(|(month, day)| DateCount { month, day, count: count_raw_solutions(month, day, all_piece_placements) })
+        .collect())
+}
+
+/// 開始日から終了日まで（両端を含む）の各日付の解の個数を、`{ month, day, count }`のリストで日付順に返す
+/// 週表示などで、日付ごとに呼び出さずに済むようにするためのもので、月をまたぐ範囲も扱える
+#[wasm_bindgen]
+pub fn count_solutions_for_range(start_month: u32, start_day: u32, end_month: u32, end_day: u32) -> Result<JsValue, JsValue> {
+    console_error_panic_hook::set_once();
+    validate_date(start_month, start_day)?;
+    validate_date(end_month, end_day)?;
+
+    let counts = count_solutions_in_range((start_month, start_day), (end_month, end_day)).map_err(|e| JsValue::from_str(&e))?;
+    Ok(serde_wasm_bindgen::to_value(&counts)?)
+}
+
+/// 月日の組 - JavaScript側には`{ month, day }`として渡す
+#[derive(Serialize, Clone, Copy)]
+struct CalendarDate {
+    month: u32,
+    day: u32,
+}
+
+/// 配置パターン`all_piece_placements`で解が1つもない日付を、1月1日から順に返す
+/// 日付ごとに最初の解が見つかった時点で探索を打ち切る
+fn unsolvable_dates_with(all_piece_placements: &[Vec<u64>]) -> Vec<CalendarDate> {
+    all_dates()
+        .filter(|&(month, day)| run_search(initial_board(month, day), all_piece_placements, &mut |_: &[u64; 8]| false))
+        .map(|(month, day)| CalendarDate { month, day })
+        .collect()
+}
+
+/// 解が1つもない日付を`{ month, day }`のリストで返す - その盤面とピースの組では表せない日付の一覧になる
+/// 標準の盤面とピース集合では全ての日付に解があるので、空のリストになる
+#[wasm_bindgen]
+pub fn unsolvable_dates() -> Result<JsValue, JsValue> {
+    console_error_panic_hook::set_once();
+
+    let all_piece_placements = compute_piece_placements();
+    Ok(serde_wasm_bindgen::to_value(&unsolvable_dates_with(all_piece_placements))?)
+}
+
+/// 解の個数が最も多い（最も易しい）日付を [月, 日] で返す
+/// 同数の日付が複数ある場合は、カレンダー上で早い日付を返す
+#[wasm_bindgen]
+pub fn easiest_date(variant: &str) -> Result<JsValue, JsValue> {
+    console_error_panic_hook::set_once();
+    check_variant(variant)?;
+
+    let counts = count_solutions_all_dates();
+    let (_, easiest) = solution_count_extremes(&counts).ok_or_else(|| JsValue::from_str("日付がありません"))?;
+    Ok(serde_wasm_bindgen::to_value(&(easiest.month, easiest.day))?)
+}
+
+/// 解の個数が最小と最大の日付
+#[derive(Serialize)]
+struct ExtremeDates {
+    hardest: DateCount,
+    easiest: DateCount,
+}
+
+/// 解の個数が最も少ない（最も難しい）日付と最も多い（最も易しい）日付を、`{ hardest, easiest }`の形で個数とともに返す
+/// 個数が同じ日付が複数ある場合は、どちらもカレンダー上で早い日付を返す
+/// 標準の盤面とピース集合では、最難は10月6日（7通り）、最易は1月25日（216通り）になる
+#[wasm_bindgen]
+pub fn extreme_dates() -> Result<JsValue, JsValue> {
+    console_error_panic_hook::set_once();
+
+    let counts = count_solutions_all_dates();
+    let (hardest, easiest) = solution_count_extremes(&counts).ok_or_else(|| JsValue::from_str("日付がありません"))?;
+    Ok(serde_wasm_bindgen::to_value(&ExtremeDates { hardest, easiest })?)
+}
+
+/// 解の個数の中央値に最も近い日付（難しすぎず易しすぎない日付）を [月, 日] で返す
+/// 日付数が偶数なので中央値は中央の2つの平均とし、距離が同じ日付が複数ある場合はカレンダー上で早い日付を返す
+#[wasm_bindgen]
+pub fn median_difficulty_date(variant: &str) -> Result<JsValue, JsValue> {
+    console_error_panic_hook::set_once();
+    check_variant(variant)?;
+
+    let (_, closest) = median_difficulty(&count_solutions_all_dates()).ok_or_else(|| JsValue::from_str("日付がありません"))?;
+    Ok(serde_wasm_bindgen::to_value(&(closest.month, closest.day))?)
+}
+
+/// 日付ごとの解の個数`counts`の中央値と、個数がそれに最も近い日付を返す（日付が無ければ`None`）
+fn median_difficulty(counts: &[DateCount]) -> Option<(f64, DateCount)> {
+    let mut sorted: Vec<u32> = counts.iter().map(|c| c.count).collect();
+    sorted.sort_unstable();
+    if sorted.is_empty() { return None; }
+    let median = (sorted[(sorted.len() - 1) / 2] + sorted[sorted.len() / 2]) as f64 / 2.0;
+
+    let closest = counts.iter().min_by(|a, b| (a.count as f64 - median).abs().total_cmp(&(b.count as f64 - median).abs()))?;
+    Some((median, *closest))
+}
+
+/// 月ごとに、その月の全日付の解の個数の平均を [月, 平均] のリストで返す
+/// 季節ごとの難易度をグラフにするための集計
+#[wasm_bindgen]
+pub fn counts_by_month(variant: &str) -> Result<JsValue, JsValue> {
+    console_error_panic_hook::set_once();
+    check_variant(variant)?;
+
+    Ok(serde_wasm_bindgen::to_value(&monthly_average_counts(&count_solutions_all_dates()))?)
+}
+
+/// 日付ごとの解の個数`counts`から、月ごとの平均を [月, 平均] のリストにする
+fn monthly_average_counts(counts: &[DateCount]) -> Vec<(u32, f64)> {
+    (1..=12).map(|month| {
+        let month_counts: Vec<u32> = counts.iter().filter(|c| c.month == month).map(|c| c.count).collect();
+        let total: u32 = month_counts.iter().sum();
+        (month, total as f64 / month_counts.len() as f64)
+    }).collect()
+}
+
+/// 全日付の解の個数の分布
+#[derive(Serialize)]
+struct CountHistogram {
+    /// 解の個数から、その個数になる日付の数への対応
+    buckets: BTreeMap<u32, u32>,
+    min: u32,
+    max: u32,
+    /// 全日付の解の個数の平均
+    mean: f64,
+}
+
+/// 日付ごとの解の個数`counts`から、個数ごとの日付の数と最小・最大・平均を集計する
+fn count_histogram(counts: &[DateCount]) -> CountHistogram {
+    let mut buckets = BTreeMap::new();
+    for c in counts { *buckets.entry(c.count).or_insert(0) += 1; }
+    let min = counts.iter().map(|c| c.count).min().unwrap_or(0);
+    let max = counts.iter().map(|c| c.count).max().unwrap_or(0);
+    let total: u32 = counts.iter().map(|c| c.count).sum();
+    let mean = if counts.is_empty() { 0.0 } else { total as f64 / counts.len() as f64 };
+    CountHistogram { buckets, min, max, mean }
+}
+
+/// 全366日の解の個数の分布を`{ buckets, min, max, mean }`で返す - `buckets`は解の個数をキー、その個数の日付の数を値とするオブジェクト
+/// 難易度の分布を、そのままヒストグラムとして描くための集計
+#[wasm_bindgen]
+pub fn solution_count_histogram() -> Result<JsValue, JsValue> {
+    console_error_panic_hook::set_once();
+
+    let histogram = count_histogram(&count_solutions_all_dates());
+    let serializer = serde_wasm_bindgen::Serializer::new().serialize_maps_as_objects(true);
+    Ok(histogram.serialize(&serializer)?)
+}
+
+// --- 盤面の対称変換 ---
+
+/// 7x7盤面の対称変換（正方形の対称群の8要素）でマス (行, 列) を移す
+/// `rot_type`の意味は`rotate_and_flip`と同じ: 4以上ならまず左右反転し、その後 (rot_type % 4) × 90度回転する
+/// 4〜7はいずれも鏡映（左右・反対角線・上下・主対角線の各軸での折り返し）になる
+fn transform_cell(rot_type: u8, r: usize, c: usize) -> (usize, usize) {
+    let c = if rot_type >= 4 { 6 - c } else { c };
+    match rot_type % 4 {
+        0 => (r, c),
+        1 => (c, 6 - r),
+        2 => (6 - r, 6 - c),
+        _ => (6 - c, r),
+    }
+}
+
+/// 盤面全体を対称変換する
+fn transform_board(board: &[Vec<i8>], rot_type: u8) -> Vec<Vec<i8>> {
+    let mut transformed = vec![vec![0i8; 7]; 7];
+    for (r, row) in board.iter().enumerate() {
+        for (c, &cell) in row.iter().enumerate() {
+            let (tr, tc) = transform_cell(rot_type, r, c);
+            transformed[tr][tc] = cell;
+        }
+    }
+    transformed
+}
+
+/// ビットマスクの盤面を対称変換する
+fn transform_mask(board: Board, rot_type: u8) -> Board {
+    let mut transformed = Board::default();
+    for r in 0..7 {
+        for c in 0..7 {
+            if board.get(r, c) {
+                let (tr, tc) = transform_cell(rot_type, r, c);
+                transformed.set(tr, tc);
+            }
+        }
+    }
+    transformed
+}
+
+/// 盤面を自分自身に移す、恒等変換以外の対称変換（`rot_type` 1〜7）を列挙する
+fn board_automorphisms(board: Board) -> Vec<u8> {
+    (1..8).filter(|&rot_type| transform_mask(board, rot_type) == board).collect()
+}
+
+/// 盤面が、いずれかの鏡映で自分自身と一致する（線対称な）かどうか
+fn is_self_symmetric(board: &[Vec<i8>]) -> bool {
+    (4..8).any(|rot_type| transform_board(board, rot_type) == board)
+}
+
+/// 解の盤面が、いずれかの鏡映で自分自身と一致する（線対称な）解だけを返す
+/// 固定の穴・日付の穴も含めて盤面全体が一致する必要があるため、穴の配置が非対称な日付では常に空になる
+#[wasm_bindgen]
+pub fn self_symmetric_solutions(month: u32, day: u32) -> Result<JsValue, JsValue> {
+    console_error_panic_hook::set_once();
+    validate_date(month, day)?;
+
+    let symmetric: Vec<Solution> = solutions_for_date(month, day).into_iter()
+        .filter(|solution| is_self_symmetric(&solution.board))
+        .collect();
+    Ok(serde_wasm_bindgen::to_value(&symmetric)?)
+}
+
+/// 盤面の対称変換で移り合う解を1つにまとめる - 各同値類からは、盤面を辞書順比較して最小の解だけを残す
+/// 使う変換は、その日付の穴の配置（固定の穴と日付の穴）を保つ変換だけ
+/// 対称変換で移した解も同じ全解のリストに含まれるので、残した解は必ず元の探索の解そのものになる
+fn deduplicate_symmetric_solutions(solutions: Vec<Solution>, month: u32, day: u32) -> Vec<Solution> {
+    let automorphisms = board_automorphisms(initial_board(month, day));
+    solutions.into_iter()
+        .filter(|solution| automorphisms.iter().all(|&rot_type| solution.board <= transform_board(&solution.board, rot_type)))
+        .collect()
+}
+
+/// 盤面の対称変換で移り合う解を重複とみなして取り除いた、指定された月日の解を返す
+/// DragonFjordの盤面では固定の穴が対称性を壊しているため、どの日付でも`solve_for_date`と同じ結果になる
+#[wasm_bindgen]
+pub fn solve_for_date_canonical(month: u32, day: u32) -> Result<JsValue, JsValue> {
+    console_error_panic_hook::set_once();
+    validate_date(month, day)?;
+
+    let solutions = deduplicate_symmetric_solutions(solutions_for_date(month, day), month, day);
+    Ok(serde_wasm_bindgen::to_value(&solutions)?)
+}
+
+/// 解の個数と、対称変換で移り合う解を1つとみなしたときの個数
+#[derive(Serialize)]
+struct UniquenessClass {
+    raw_count: u32,
+    canonical_count: u32,
+    /// 対称変換を除いて解がちょうど1つかどうか
+    is_unique: bool,
+}
+
+/// 指定された月日の解の個数を、`deduplicate_symmetric_solutions`で対称な解をまとめる前と後で数える
+fn uniqueness_class_for_date(month: u32, day: u32) -> UniquenessClass {
+    uniqueness_class_of(solutions_for_date(month, day), month, day)
+}
+
+/// 指定された月日の解のリスト`solutions`の個数を、対称な解をまとめる前と後で数える
+fn uniqueness_class_of(solutions: Vec<Solution>, month: u32, day: u32) -> UniquenessClass {
+    let raw_count = solutions.len() as u32;
+    let canonical_count = deduplicate_symmetric_solutions(solutions, month, day).len() as u32;
+    UniquenessClass { raw_count, canonical_count, is_unique: canonical_count == 1 }
+}
+
+/// 指定された月日が、対称変換を除いて解がただ1つの日付かどうかを`{ raw_count, canonical_count, is_unique }`で返す
+/// 穴の配置を保つ対称変換がない日付（DragonFjordの盤面では全ての日付）では、`raw_count`と`canonical_count`は等しい
+#[wasm_bindgen]
+pub fn uniqueness_class(month: u32, day: u32) -> Result<JsValue, JsValue> {
+    console_error_panic_hook::set_once();
+    validate_date(month, day)?;
+
+    Ok(serde_wasm_bindgen::to_value(&uniqueness_class_for_date(month, day))?)
+}
+
+// --- 代表解の比較 ---
+
+/// 日付の代表解を返す - 盤面を行優先で辞書順比較したときに最小となる解
+/// 探索順序に依存しないので、常に同じ解が選ばれる（解が無い日付では`None`）
+fn canonical_solution_for_date(month: u32, day: u32) -> Option<Solution> {
+    solutions_for_date(month, day).into_iter().min_by(|a, b| a.board.cmp(&b.board))
+}
+
+/// 2つの盤面で、異なるピースIDが割り当てられているマスの数を数える
+/// どちらかで日付の穴(-1)になっているマスは比較から除く
+fn board_distance(board_a: &[Vec<i8>], board_b: &[Vec<i8>]) -> usize {
+    board_a.iter().flatten().zip(board_b.iter().flatten())
+        .filter(|&(&a, &b)| a != -1 && b != -1 && a != b)
+        .count()
+}
+
+/// 2つの日付の代表解どうしの距離（ピースIDが異なるマスの数）を返す
+/// 「昨日と今日の解がどれだけ違うか」といった指標に使う
+#[wasm_bindgen]
+pub fn canonical_board_distance(month_a: u32, day_a: u32, month_b: u32, day_b: u32) -> Result<usize, JsValue> {
+    console_error_panic_hook::set_once();
+    validate_date(month_a, day_a)?;
+    validate_date(month_b, day_b)?;
+
+    let no_solution = |month: u32, day: u32| JsValue::from_str(&format!("{}月{}日には解がありません", month, day));
+    let solution_a = canonical_solution_for_date(month_a, day_a).ok_or_else(|| no_solution(month_a, day_a))?;
+    let solution_b = canonical_solution_for_date(month_b, day_b).ok_or_else(|| no_solution(month_b, day_b))?;
+    Ok(board_distance(&solution_a.board, &solution_b.board))
+}
+
+// --- 使わないピースを許す探索 ---
+
+/// 使わないピースがあってもよいバックトラッキング探索
+/// 各ピースについて「どこかに置く」か「使わない」かを選び、盤面がちょうど埋まったものを解とする
+/// 使わなかったピースは`None`として記録する
+fn find_covers_recursive(
+    piece_idx: usize, current_board: Board, used_placements: &mut Vec<Option<u64>>,
+    all_piece_placements: &[Vec<u64>], solutions: &mut Vec<Vec<Option<u64>>>,
+) {
+    // ベースケース: 全ピースについて選び終えた時点で、盤面が埋まっていれば解として保存
+    if piece_idx == 8 {
+        if current_board.is_full() { solutions.push(used_placements.clone()); }
+        return;
+    }
+
+    // 枝刈り: 残りのピースを全部使っても空きマスを埋めきれないなら打ち切る
+    let remaining_area: u32 = all_piece_placements[piece_idx..].iter().map(|p| p.first().map_or(0, |m| m.count_ones())).sum();
+    if remaining_area < current_board.empty_cells() { return; }
+
+    // どちらの場合も、以降の島はまだ選んでいない後ろのピースだけで埋める必要がある
+    let remaining_sizes = || all_piece_placements[piece_idx + 1..].iter().map(|p| p.first().map_or(0, |m| m.count_ones() as usize));
+
+    // 1. 現在のピースを置く場合
+    for &placement_mask in &all_piece_placements[piece_idx] {
+        if !current_board.overlaps(&Board(placement_mask)) {
+            let new_board = current_board.union(&Board(placement_mask));
+            if judge_connected_component(new_board, remaining_sizes()) {
+                used_placements.push(Some(placement_mask));
+                find_covers_recursive(piece_idx + 1, new_board, used_placements, all_piece_placements, solutions);
+                used_placements.pop();
+            }
+        }
+    }
+
+    // 2. 現在のピースを使わない場合
+    if judge_connected_component(current_board, remaining_sizes()) {
+        used_placements.push(None);
+        find_covers_recursive(piece_idx + 1, current_board, used_placements, all_piece_placements, solutions);
+        used_placements.pop();
+    }
+}
+
+/// 埋まっているマスを1、空きマスを0とした7x7の盤面を受け取り、ピースの一部だけを使ってちょうど埋める解を全て返す
+/// ピースの合計面積が盤面より大きい「ピースが余る」パズル用 - 使わなかったピースは盤面に現れない
+/// 返す盤面では、入力で埋まっていたマスを-1で表す
+#[wasm_bindgen]
+pub fn solve_with_optional_pieces(blocked: JsValue) -> Result<JsValue, JsValue> {
+    console_error_panic_hook::set_once();
+
+    let blocked: Vec<Vec<u8>> = serde_wasm_bindgen::from_value(blocked)?;
+    if blocked.len() != 7 || blocked.iter().any(|row| row.len() != 7) {
+        return Err(JsValue::from_str("盤面は7x7である必要があります"));
+    }
+    let solutions = solutions_with_optional_pieces(Board::from_grid(&blocked)).map_err(|e| JsValue::from_str(&e))?;
+    Ok(serde_wasm_bindgen::to_value(&solutions)?)
+}
+
+/// 盤面`start_board`の空きマスを、ピースの一部だけを使ってちょうど埋める解を全て求める
+/// ピースを全部使っても空きマスを埋めきれない盤面はエラーメッセージを返す
+fn solutions_with_optional_pieces(start_board: Board) -> Result<Vec<Solution>, String> {
+    let all_piece_placements = compute_piece_placements();
+    // ピースを全部使っても空きマスを埋めきれない盤面は解きようがない
+    let total_piece_area: u32 = all_piece_placements.iter().map(|p| p[0].count_ones()).sum();
+    let empty_area = start_board.empty_cells();
+    if empty_area > total_piece_area {
+        return Err(format!("空きマス{}個に対してピースの合計面積が{}しかありません", empty_area, total_piece_area));
+    }
+
+    let mut found_raw_solutions = Vec::new();
+    find_covers_recursive(0, start_board, &mut Vec::new(), all_piece_placements, &mut found_raw_solutions);
+    Ok(found_raw_solutions.iter().map(|masks| masks_to_solution_with_blocked(masks, start_board, &get_initial_pieces())).collect())
+}
+
+/// 任意の盤面での探索結果を`Solution`に変換する - 最初から埋まっていたマスを-1、使わなかったピース(`None`)は盤面に現れない
+/// `shapes`は各ピースの基本形で、向きの集計に使う
+fn masks_to_solution_with_blocked(masks: &[Option<u64>], blocked: Board, shapes: &[Vec<Vec<u8>>]) -> Solution {
+    let mut board: Vec<Vec<i8>> = (0..7).map(|r| (0..7).map(|c| if blocked.get(r, c) { -1 } else { 0 }).collect()).collect();
+    for (piece_id, mask) in masks.iter().enumerate() {
+        let Some(mask) = mask else { continue };
+        for i in 0..49 {
+            if (mask >> i) & 1 == 1 { board[i / 7][i % 7] = (piece_id + 1) as i8; }
+        }
+    }
+    let orientations_used = masks.iter().zip(shapes)
+        .filter(|&(mask, shape)| mask.is_some_and(|mask| orientation_index(shape, mask).is_some_and(|i| i > 0)))
+        .count() as u8;
+    Solution { board, orientations_used }
+}
+
+// --- 独自のピース集合での探索 ---
+
+/// 独自のピース集合に含まれる1個分のピース
+struct CustomPiece {
+    /// 盤面内の全ての配置パターン
+    placements: Vec<u64>,
+    /// ピースのマス数
+    size: usize,
+    /// 直前のピースと同じ形のコピーかどうか
+    is_copy: bool,
+}
+
+/// 空きマスの各島の大きさを、残りのピースのサイズの組み合わせ（部分和）で作れるかを調べる
+/// ピースのサイズが5と6に限られない独自のピース集合のための、`judge_connected_component`の一般化
+/// 盤面は幅7列・高さ`rows`行として扱う
+fn judge_islands_for_sizes(board: Board, remaining_sizes: &[usize], rows: usize) -> bool {
+    judge_islands_with_slack(board, remaining_sizes, rows, 0)
+}
+
+/// `judge_islands_for_sizes`の、空きマスが最大`slack`個まで埋まらずに残ってもよい版
+/// 各島について、残りのピースの部分和で埋められる最大のマス数との差（埋めきれないマス数）を求め、その合計が`slack`を超えたら手詰まりとする
+/// 島ごとに全ピースを使える前提で数えるので、埋めきれないマス数を多く見積もることはない
+fn judge_islands_with_slack(board: Board, remaining_sizes: &[usize], rows: usize, slack: usize) -> bool {
+    let board_mask = board.0;
+    let cell_count = rows * 7;
+    // 残りのピースで作れる合計マス数の集合をビット集合で求める（bit kが立っていればkマスを作れる）
+    let mut reachable_sums = 1u64;
+    for &size in remaining_sizes { reachable_sums |= reachable_sums << size; }
+
+    SIZED_ISLAND_UNION_FIND.with_borrow_mut(|uf| {
+        uf.reset_with_len(cell_count);
+        for i in 0..cell_count {
+            if (board_mask >> i) & 1 == 0 {
+                if (i + 1) % 7 != 0 && (board_mask >> (i + 1)) & 1 == 0 { uf.union(i, i + 1); }
+                if i + 7 < cell_count && (board_mask >> (i + 7)) & 1 == 0 { uf.union(i, i + 7); }
+            }
+        }
+        let mut waste = 0;
+        for (root, size) in uf.groups() {
+            if (board_mask >> root) & 1 == 0 {
+                let fillable = (reachable_sums & ((1u64 << (size + 1)) - 1)).ilog2() as usize;
+                waste += size - fillable;
+                if waste > slack { return false; }
+            }
+        }
+        true
+    })
+}
+
+thread_local! {
+    /// `judge_islands_with_slack`が使い回すUnion-Find木 - 盤面の行数が変わっても、64マス分の領域を確保し直さずに使う
+    static SIZED_ISLAND_UNION_FIND: std::cell::RefCell<UnionFind> = std::cell::RefCell::new(UnionFind::new(64));
+}
+
+/// 独自のピース集合で全解を探索する再帰関数
+/// 同じ形のピースが複数ある場合、2個目以降のコピーは直前のコピーより後ろの配置番号だけを使う
+/// これにより、同じ形のピースを入れ替えただけの重複解（コピー数の階乗倍）を数えずに済む
+/// 盤面は幅7列・高さ`rows`行として扱う
+fn find_solutions_custom(
+    piece_idx: usize, current_board: Board, used_indices: &mut Vec<usize>,
+    pieces: &[CustomPiece], rows: usize, solutions: &mut Vec<Vec<u64>>,
+) {
+    if piece_idx == pieces.len() {
+        solutions.push(used_indices.iter().zip(pieces).map(|(&i, piece)| piece.placements[i]).collect());
+        return;
+    }
+
+    let piece = &pieces[piece_idx];
+    let first_index = if piece.is_copy { used_indices[piece_idx - 1] + 1 } else { 0 };
+    let remaining_sizes: Vec<usize> = pieces[piece_idx + 1..].iter().map(|p| p.size).collect();
+    for (i, &placement_mask) in piece.placements.iter().enumerate().skip(first_index) {
+        if !current_board.overlaps(&Board(placement_mask)) {
+            let new_board = current_board.union(&Board(placement_mask));
+            if judge_islands_for_sizes(new_board, &remaining_sizes, rows) {
+                used_indices.push(i);
+                find_solutions_custom(piece_idx + 1, new_board, used_indices, pieces, rows, solutions);
+                used_indices.pop();
+            }
+        }
+    }
+}
+
+/// `[[形状, 個数], ...]`の形式のピース集合を検証し、コピーも1個ずつ展開した探索用のピースのリストにする
+fn build_custom_pieces(piece_counts: &[(Vec<Vec<u8>>, u32)]) -> Result<Vec<CustomPiece>, String> {
+    let mut total_area = 0u64;
+    for (shape, count) in piece_counts {
+        if shape.is_empty() || shape[0].is_empty() || shape.iter().any(|row| row.len() != shape[0].len() || row.iter().any(|&cell| cell > 1)) {
+            return Err("ピースの形状は0/1の長方形の2次元配列である必要があります".to_string());
+        }
+        let size = shape.iter().flatten().filter(|&&cell| cell == 1).count();
+        if size == 0 { return Err("ピースの形状には埋まっているマスが1つ以上必要です".to_string()); }
+        total_area += size as u64 * *count as u64;
+    }
+    // コピーを展開する前に面積を調べて、巨大な個数で配置パターンの複製にメモリを使い果たさないようにする
+    if total_area > 49 { return Err(format!("ピースの合計面積{}が盤面のマス数49を超えています", total_area)); }
+
+    let mut custom_pieces = Vec::new();
+    for (shape, count) in piece_counts {
+        let placements = shape_placements(shape);
+        let size = shape.iter().flatten().filter(|&&cell| cell == 1).count();
+        for copy in 0..*count {
+            custom_pieces.push(CustomPiece { placements: placements.clone(), size, is_copy: copy > 0 });
+        }
+    }
+    Ok(custom_pieces)
+}
+
+/// ピースの合計面積が盤面の空きマスの数と一致するかを調べる - 一致しなければ盤面をちょうど埋めることはできない
+fn check_custom_area(custom_pieces: &[CustomPiece], start_board: Board) -> Result<(), String> {
+    let total_piece_area: usize = custom_pieces.iter().map(|p| p.size).sum();
+    let empty_area = start_board.empty_cells() as usize;
+    if total_piece_area != empty_area {
+        return Err(format!("ピースの合計面積{}が空きマスの数{}と一致しません", total_piece_area, empty_area));
+    }
+    Ok(())
+}
+
+/// 独自のピース集合と各ピースの個数 `[[形状, 個数], ...]` を受け取り、指定された月日の全解を返す
+/// 形状は`get_initial_pieces`と同じ0/1の2次元配列で、盤面のIDはコピーも含めた通し番号(1〜)になる
+/// ピースの合計面積が空きマスの数と一致しない場合はエラーを返す
+#[wasm_bindgen]
+pub fn solve_for_date_with_pieces(month: u32, day: u32, pieces: JsValue) -> Result<JsValue, JsValue> {
+    console_error_panic_hook::set_once();
+    validate_date(month, day)?;
+
+    let piece_counts: Vec<(Vec<Vec<u8>>, u32)> = serde_wasm_bindgen::from_value(pieces)?;
+    let solutions = solutions_with_piece_counts(month, day, &piece_counts).map_err(|e| JsValue::from_str(&e))?;
+    Ok(serde_wasm_bindgen::to_value(&solutions)?)
+}
+
+/// `[[形状, 個数], ...]`の形式のピース集合で、指定された月日の全解を求める
+fn solutions_with_piece_counts(month: u32, day: u32, piece_counts: &[(Vec<Vec<u8>>, u32)]) -> Result<Vec<Solution>, String> {
+    let custom_pieces = build_custom_pieces(piece_counts)?;
+    let start_board = initial_board(month, day);
+    check_custom_area(&custom_pieces, start_board)?;
+
+    let mut found_raw_solutions = Vec::new();
+    find_solutions_custom(0, start_board, &mut Vec::new(), &custom_pieces, 7, &mut found_raw_solutions);
+
+    // 盤面のIDはコピーも含めた通し番号なので、向きの集計にもコピーを展開した形状のリストを使う
+    let shapes: Vec<Vec<Vec<u8>>> = piece_counts.iter().flat_map(|(shape, count)| (0..*count).map(move |_| shape.clone())).collect();
+    Ok(found_raw_solutions.iter().map(|masks| {
+        let mut solution = masks_to_solution(masks, month, day);
+        solution.orientations_used = count_orientations_used(masks, &shapes);
+        solution
+    }).collect())
+}
+
+/// `get_initial_pieces`と同じ形式（ピースごとの0/1の2次元配列）の独自のピース集合で、指定された月日の全解を返す
+/// 回転・反転と配置パターン、ピースのサイズは渡されたピースから計算し直し、盤面のIDはリストの順の通し番号(1〜)になる
+/// ピースの合計面積と穴の数の和が49にならない場合はエラーを返す
+/// 標準のピース集合を渡すと`solve_for_date`と同じ解の集合になる（探索方法が異なるので並び順は異なる）
+#[wasm_bindgen]
+pub fn solve_for_date_custom(month: u32, day: u32, pieces: JsValue) -> Result<JsValue, JsValue> {
+    console_error_panic_hook::set_once();
+    validate_date(month, day)?;
+
+    let pieces: Vec<Vec<Vec<u8>>> = serde_wasm_bindgen::from_value(pieces)?;
+    let piece_counts: Vec<(Vec<Vec<u8>>, u32)> = pieces.into_iter().map(|shape| (shape, 1)).collect();
+    let solutions = solutions_with_piece_counts(month, day, &piece_counts).map_err(|e| JsValue::from_str(&e))?;
+    Ok(serde_wasm_bindgen::to_value(&solutions)?)
+}
+
+// --- 日付の穴を決めない探索 ---
+
+/// 固定の穴だけを埋めた盤面に全ピースを置く方法を探索する再帰関数 - 最初の空きマスを「空けておく」か、ピースで覆うかで分岐する
+/// ピースの合計は41マスで空きマスは43個あるので、ちょうど`gaps_left`(=2)個のマスが埋まらずに残る
+/// 解が見つかるたびに、ピースごとの配置ビットマスクと空けておいたマスのビットマスクを`solutions`に保存する
+fn find_tilings_with_gaps(
+    current_board: Board, used_placements: &mut [u64; 8], gaps: u64, gaps_left: usize,
+    cell_placements: &[Vec<(usize, u64)>], piece_sizes: &[usize; 8], solutions: &mut Vec<([u64; 8], u64)>,
+) {
+    let Some(cell) = first_empty_cell(current_board) else {
+        if gaps_left == 0 { solutions.push((*used_placements, gaps)); }
+        return;
+    };
+    let remaining_sizes = |used_placements: &[u64; 8]| -> Vec<usize> {
+        (0..8).filter(|&piece_idx| used_placements[piece_idx] == 0).map(|piece_idx| piece_sizes[piece_idx]).collect()
+    };
+
+    // 1. 最初の空きマスを空けておく場合
+    if gaps_left > 0 {
+        let new_board = current_board.union(&Board(1u64 << cell));
+        if judge_islands_with_slack(new_board, &remaining_sizes(used_placements), 7, gaps_left - 1) {
+            find_tilings_with_gaps(new_board, used_placements, gaps | (1u64 << cell), gaps_left - 1, cell_placements, piece_sizes, solutions);
+        }
+    }
+
+    // 2. 最初の空きマスをピースで覆う場合
+    for &(piece_idx, placement_mask) in &cell_placements[cell] {
+        if used_placements[piece_idx] == 0 && !current_board.overlaps(&Board(placement_mask)) {
+            let new_board = current_board.union(&Board(placement_mask));
+            used_placements[piece_idx] = placement_mask;
+            if judge_islands_with_slack(new_board, &remaining_sizes(used_placements), 7, gaps_left) {
+                find_tilings_with_gaps(new_board, used_placements, gaps, gaps_left, cell_placements, piece_sizes, solutions);
+            }
+            used_placements[piece_idx] = 0;
+        }
+    }
+}
+
+/// 日付の穴を空けずに、固定の6個の穴だけの盤面へ全ピースを置く方法を全て求める
+/// 空きマス43個に対してピースは41マスなので、全ての置き方でちょうど2マスが埋まらずに残り、返す盤面ではそれを-1で表す
+fn empty_board_tilings() -> Vec<Solution> {
+    let all_piece_placements = compute_piece_placements();
+    let piece_sizes: [usize; 8] = std::array::from_fn(|piece_idx| all_piece_placements[piece_idx][0].count_ones() as usize);
+    let mut start_board = Board::default();
+    for &(r, c) in &FIXED_HOLES { start_board.set(r, c); }
+    let gap_count = (start_board.empty_cells() as usize) - piece_sizes.iter().sum::<usize>();
+
+    let mut found_tilings = Vec::new();
+    find_tilings_with_gaps(start_board, &mut [0; 8], 0, gap_count, &cell_placement_index(all_piece_placements), &piece_sizes, &mut found_tilings);
+    found_tilings.iter().map(|(masks, gaps)| {
+        let mut board = vec![vec![0i8; 7]; 7];
+        for (piece_id, &mask) in masks.iter().enumerate() {
+            for i in 0..49 {
+                if (mask >> i) & 1 == 1 { board[i / 7][i % 7] = (piece_id + 1) as i8; }
+            }
+        }
+        for i in 0..49 {
+            if (gaps >> i) & 1 == 1 { board[i / 7][i % 7] = -1; }
+        }
+        Solution { board, orientations_used: standard_orientations_used(masks) }
+    }).collect()
+}
+
+/// 固定の6個の穴だけを空けた盤面に、8個のピースを全て置く方法を全て返す（日付は指定しない）
+/// ピースの合計は41マスで、固定の穴を除いた43マスより2マス少ないため、全マスを埋める置き方は存在しない
+/// 代わりに、必ず残る2マスを-1で表した置き方を全て返す（標準のピース集合では59787通り）
+/// ある日付の解は、残る2マスがちょうどその月と日のマスになっている置き方と一致するので、
+/// 各日付の`solve_for_date`の結果は、この一覧から-1の位置で絞り込んだものになる
+#[wasm_bindgen]
+pub fn solve_empty_board() -> Result<JsValue, JsValue> {
+    console_error_panic_hook::set_once();
+
+    Ok(serde_wasm_bindgen::to_value(&empty_board_tilings())?)
+}
+
+// --- 一部のピースを除いた探索 ---
+
+/// 指定したピースだけを、重ならないように全て置く方法を探索する再帰関数
+/// `piece_indices`のピースを順に置き、盤面を埋めきる必要はない - 埋まらずに残るマスは最大`slack`個
+fn find_subset_placements(
+    depth: usize, current_board: Board, used_placements: &mut Vec<u64>, piece_indices: &[usize],
+    all_piece_placements: &[Vec<u64>], slack: usize, solutions: &mut Vec<Vec<u64>>,
+) {
+    if depth == piece_indices.len() {
+        solutions.push(used_placements.clone());
+        return;
+    }
+
+    let remaining_sizes: Vec<usize> = piece_indices[depth + 1..].iter().map(|&i| all_piece_placements[i][0].count_ones() as usize).collect();
+    for &placement_mask in &all_piece_placements[piece_indices[depth]] {
+        if !current_board.overlaps(&Board(placement_mask)) {
+            let new_board = current_board.union(&Board(placement_mask));
+            if judge_islands_with_slack(new_board, &remaining_sizes, 7, slack) {
+                used_placements.push(placement_mask);
+                find_subset_placements(depth + 1, new_board, used_placements, piece_indices, all_piece_placements, slack, solutions);
+                used_placements.pop();
+            }
+        }
+    }
+}
+
+/// ピースID`excluded_piece_ids`を除いた残りのピースを、穴と重ならず互いに重ならないように全て置く方法を求める
+/// ピースが足りない分だけ盤面には空きマスが残り、返す盤面ではそのマスも固定の穴と同じ0で表す
+/// 除くピースが無ければ、`solve_for_date`と同じ解の集合になる（並び順は異なる）
+fn solutions_for_subset(month: u32, day: u32, excluded_piece_ids: &[u8]) -> Result<Vec<Solution>, String> {
+    if let Some(&piece_id) = excluded_piece_ids.iter().find(|&&id| !(1..=8).contains(&id)) {
+        return Err(format!("ピースのIDは1〜8で指定してください: {}", piece_id));
+    }
+
+    let all_piece_placements = compute_piece_placements();
+    let piece_indices: Vec<usize> = (0..8).filter(|&i| !excluded_piece_ids.contains(&(i as u8 + 1))).collect();
+    let start_board = initial_board(month, day);
+    let remaining_area: u32 = piece_indices.iter().map(|&i| all_piece_placements[i][0].count_ones()).sum();
+    let slack = (start_board.empty_cells() - remaining_area) as usize;
+
+    let mut found_raw_solutions = Vec::new();
+    find_subset_placements(0, start_board, &mut Vec::new(), &piece_indices, all_piece_placements, slack, &mut found_raw_solutions);
+    Ok(found_raw_solutions.iter().map(|masks| {
+        let mut piece_masks = vec![None; 8];
+        for (&piece_idx, &mask) in piece_indices.iter().zip(masks) { piece_masks[piece_idx] = Some(mask); }
+        let mut solution = masks_to_solution_with_blocked(&piece_masks, Board::default(), &get_initial_pieces());
+        let [(month_r, month_c), (day_r, day_c)] = [month_cell(month), day_cell(day)];
+        solution.board[month_r][month_c] = -1;
+        solution.board[day_r][day_c] = -1;
+        solution
+    }).collect())
+}
+
+/// ピースID`excluded_piece_ids`のピースを除き、残りのピースだけで指定された月日の盤面に置く方法を全て返す
+/// 残りのピースは全て置くが盤面を埋めきる必要はなく、埋まらなかったマスは0のまま残る
+/// ピースが欠けたパズルで、どの日付がまだ解けるかを調べるのに使う
+#[wasm_bindgen]
+pub fn solve_for_date_subset(month: u32, day: u32, excluded_piece_ids: JsValue) -> Result<JsValue, JsValue> {
+    console_error_panic_hook::set_once();
+    validate_date(month, day)?;
+
+    let excluded_piece_ids: Vec<u8> = serde_wasm_bindgen::from_value(excluded_piece_ids)?;
+    let solutions = solutions_for_subset(month, day, &excluded_piece_ids).map_err(|e| JsValue::from_str(&e))?;
+    Ok(serde_wasm_bindgen::to_value(&solutions)?)
+}
+
+// --- ランダムな解 ---
+
+/// シード付きの小さな疑似乱数生成器（xorshift64） - 同じシードからは常に同じ乱数列が得られる
+struct XorShift64(u64);
+
+impl XorShift64 {
+    /// シードから生成器を作る - xorshiftは状態0から抜け出せないので、シードを定数と混ぜて0を避ける
+    fn new(seed: u64) -> XorShift64 {
+        let state = seed ^ 0x9E37_79B9_7F4A_7C15;
+        XorShift64(if state == 0 { 0x9E37_79B9_7F4A_7C15 } else { state })
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// Fisher-Yatesのシャッフルで、リストの並びを疑似乱数で並べ替える
+    fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, (self.next() % (i as u64 + 1)) as usize);
+        }
+    }
+}
+
+/// 配置パターン`all_piece_placements`を試す順序をシードで並べ替えて探索し、最初に見つかった解を返す（解が無い日付では`None`）
+fn random_solution(month: u32, day: u32, seed: u64, all_piece_placements: &[Vec<u64>]) -> Option<Solution> {
+    let mut cell_placements = cell_placement_index(all_piece_placements);
+    let mut rng = XorShift64::new(seed);
+    for placements in &mut cell_placements { rng.shuffle(placements); }
+
+    let mut found = None;
+    run_indexed_search(initial_board(month, day), [0; 8], &cell_placements, &mut |masks: &[u64; 8]| {
+        found = Some(masks_to_solution(masks, month, day));
+        false
+    });
+    found
+}
+
+/// 指定された月日の解を1つ、シード`seed`に応じてランダムに選んで返す - 「今日のサプライズ」向け
+/// 同じ (月, 日, シード) なら常に同じ解が返り、解が複数ある日付ではシードによって異なる解が返る
+/// 解が存在しない日付では`null`を返す
+#[wasm_bindgen]
+pub fn random_solution_for_date(month: u32, day: u32, seed: u64) -> Result<JsValue, JsValue> {
+    console_error_panic_hook::set_once();
+    validate_date(month, day)?;
+
+    match random_solution(month, day, seed, compute_piece_placements()) {
+        Some(solution) => Ok(serde_wasm_bindgen::to_value(&solution)?),
+        None => Ok(JsValue::NULL),
+    }
+}
+
+// --- 盤面の解析 ---
+
+/// 指定された月日で、いずれかのピースの配置が1つ以上かぶさることのできるマスのビットマスクを返す
+/// 穴と重ならない全配置パターンのORで、bit (行 * 7 + 列) が立っているマスは何かしらのピースで埋められる
+/// JavaScript側では`BigInt`として受け取る
+#[wasm_bindgen]
+pub fn fillable_cells_mask(month: u32, day: u32) -> Result<u64, JsValue> {
+    console_error_panic_hook::set_once();
+    validate_date(month, day)?;
+
+    Ok(reachable_cells(initial_board(month, day).0, compute_piece_placements()))
+}
+
+/// 埋まっているマスが`board_mask`の盤面で、配置パターン`all_piece_placements`のいずれかがかぶさることのできるマスのビットマスク
+/// `board_mask`と重ならない全配置パターンのOR
+fn reachable_cells(board_mask: u64, all_piece_placements: &[Vec<u64>]) -> u64 {
+    all_piece_placements.iter().flatten()
+        .filter(|&&placement_mask| placement_mask & board_mask == 0)
+        .fold(0, |mask, &placement_mask| mask | placement_mask)
+}
+
+/// 埋まっているマスが`board_mask`の盤面で、空いているのにどの配置パターンもかぶさることのできないマスを (行, 列) のリストで返す
+fn unreachable_cells(board_mask: u64, all_piece_placements: &[Vec<u64>]) -> Vec<(usize, usize)> {
+    let unreachable = !board_mask & !reachable_cells(board_mask, all_piece_placements) & FULL_BOARD_MASK;
+    (0..49).filter(|&bit| unreachable & (1u64 << bit) != 0).map(|bit| (bit / 7, bit % 7)).collect()
+}
+
+/// 指定された月日で、空いているのにどのピースの配置もかぶさることのできないマスを [行, 列] のリストで返す
+/// 1つでもあればその日付は解けないことが確定する - 標準のピース集合と盤面では常に空のリストになる
+#[wasm_bindgen]
+pub fn find_unreachable_cells(month: u32, day: u32) -> Result<JsValue, JsValue> {
+    console_error_panic_hook::set_once();
+    validate_date(month, day)?;
+
+    let cells = unreachable_cells(initial_board(month, day).0, compute_piece_placements());
+    Ok(serde_wasm_bindgen::to_value(&cells)?)
+}
+
+/// 盤面の中心 (3, 3) を覆う、穴と重ならない全ての配置を (ピース番号, 配置ビットマスク) のリストで返す
+/// 「真ん中から埋める」方針で最初に置くピースの候補の一覧になる
+#[wasm_bindgen]
+pub fn center_placements(month: u32, day: u32) -> Result<JsValue, JsValue> {
+    console_error_panic_hook::set_once();
+    validate_date(month, day)?;
+
+    Ok(serde_wasm_bindgen::to_value(&center_placements_for_date(month, day))?)
+}
+
+/// 指定された月日で、盤面の中心 (3, 3) を覆い穴と重ならない配置を (ピース番号, 配置ビットマスク) のリストで求める
+fn center_placements_for_date(month: u32, day: u32) -> Vec<(usize, u64)> {
+    let start_board = initial_board(month, day);
+    let center_mask = 1u64 << (3 * 7 + 3);
+    compute_piece_placements().iter().enumerate()
+        .flat_map(|(piece_idx, placements)| placements.iter().map(move |&placement_mask| (piece_idx, placement_mask)))
+        .filter(|&(_, placement_mask)| placement_mask & center_mask != 0 && !start_board.overlaps(&Board(placement_mask)))
+        .collect()
+}
+
+/// `find_solutions_recursive`と同じ探索を行い、深さ（置いたピースの個数）ごとに実際に潜った子ノードの数を数える
+/// 重ならず、枝刈りも通過した配置だけを「試した配置」として数える
+/// `used_pieces`のbit iが立っていれば、ピースiは配置済み
+fn profile_recursive(
+    depth: usize, current_board: Board, used_pieces: u8,
+    cell_placements: &[Vec<(usize, u64)>], piece_sizes: &[usize; 8], profile: &mut Vec<u64>,
+) {
+    let Some(cell) = first_empty_cell(current_board) else { return };
+
+    for &(piece_idx, placement_mask) in &cell_placements[cell] {
+        if (used_pieces >> piece_idx) & 1 == 0 && !current_board.overlaps(&Board(placement_mask)) {
+            let new_board = current_board.union(&Board(placement_mask));
+            let new_used_pieces = used_pieces | (1 << piece_idx);
+            let remaining_sizes = (0..8).filter(|&i| (new_used_pieces >> i) & 1 == 0).map(|i| piece_sizes[i]);
+            if judge_connected_component(new_board, remaining_sizes) {
+                profile[depth] += 1;
+                profile_recursive(depth + 1, new_board, new_used_pieces, cell_placements, piece_sizes, profile);
+            }
+        }
+    }
+}
+
+/// 探索木の深さ（0〜7）ごとの分岐数を返す
+/// 要素kは、k+1個目のピースを置いた子ノードの総数で、探索木がどこで最も広がるかの分析に使う
+/// 最後の要素は解の個数と一致する
+#[wasm_bindgen]
+pub fn branching_profile(month: u32, day: u32) -> Result<JsValue, JsValue> {
+    console_error_panic_hook::set_once();
+    validate_date(month, day)?;
+
+    Ok(serde_wasm_bindgen::to_value(&branching_profile_for_date(month, day))?)
+}
+
+/// 指定された月日の探索木について、深さごとの子ノードの数を数える
+fn branching_profile_for_date(month: u32, day: u32) -> Vec<u64> {
+    let cell_placements = cell_placement_index(compute_piece_placements());
+    let mut profile = vec![0u64; 8];
+    profile_recursive(0, initial_board(month, day), 0, &cell_placements, &indexed_piece_sizes(&cell_placements), &mut profile);
+    profile
+}
+
+/// 固定の穴と日付の穴を合わせた穴の配置が、恒等変換以外の対称変換で不変になる日付を [月, 日] のリストで返す
+/// このような日付では、解が対称変換で移り合うペアになる
+/// DragonFjordの盤面は固定の穴が対称性を壊しているため、結果は空になる
+#[wasm_bindgen]
+pub fn symmetric_dates(variant: &str) -> Result<JsValue, JsValue> {
+    console_error_panic_hook::set_once();
+    check_variant(variant)?;
+
+    Ok(serde_wasm_bindgen::to_value(&symmetric_dates_with(&FIXED_HOLES))?)
+}
+
+/// 固定の穴が`fixed_holes`の盤面で、穴の配置が恒等変換以外の対称変換で不変になる日付を求める
+fn symmetric_dates_with(fixed_holes: &[(usize, usize)]) -> Vec<(u32, u32)> {
+    all_dates()
+        .filter(|&(month, day)| {
+            let mut board = Board::default();
+            for &(r, c) in fixed_holes.iter().chain(&[month_cell(month), day_cell(day)]) { board.set(r, c); }
+            !board_automorphisms(board).is_empty()
+        })
+        .collect()
+}
+
+/// 解のリストから、ピースごとに各配置パターンが何個の解で使われているかを集計する
+/// 戻り値の要素iは、i番目のピースの「配置ビットマスク → 使われた解の個数」の対応表 - 置かれていない（ビットマスクが0の）ピースは数えない
+fn placement_frequencies(raw_solutions: &[[u64; 8]]) -> Vec<HashMap<u64, u32>> {
+    let mut frequencies = vec![HashMap::new(); 8];
+    for masks in raw_solutions {
+        for (piece_idx, &mask) in masks.iter().enumerate().filter(|&(_, &mask)| mask != 0) {
+            *frequencies[piece_idx].entry(mask).or_insert(0) += 1;
+        }
+    }
+    frequencies
+}
+
+/// ピースごとに、全解を通して現れる異なる配置パターンの数（柔軟性）を返す
+/// 値が小さいピースほど置き場所が限られているので、最初に置くピースのヒントになる
+#[wasm_bindgen]
+pub fn piece_flexibility(month: u32, day: u32) -> Result<JsValue, JsValue> {
+    console_error_panic_hook::set_once();
+    validate_date(month, day)?;
+
+    let all_piece_placements = compute_piece_placements();
+    let raw_solutions = find_raw_solutions(month, day, all_piece_placements);
+    let flexibility: Vec<usize> = placement_frequencies(&raw_solutions).iter().map(|f| f.len()).collect();
+    Ok(serde_wasm_bindgen::to_value(&flexibility)?)
+}
+
+/// 解のリストから、マスごとに各ピースがそのマスを覆っている解の個数を集計する
+/// 戻り値の`[r][c][p]`は、ピースp+1がマス (r, c) を覆っている解の個数 - 穴のマスは全て0になる
+fn cell_piece_counts(raw_solutions: &[[u64; 8]]) -> Vec<Vec<[u32; 8]>> {
+    let mut counts = vec![vec![[0u32; 8]; 7]; 7];
+    for masks in raw_solutions {
+        for (piece_idx, &mask) in masks.iter().enumerate() {
+            for bit in (0..49).filter(|&bit| mask & (1u64 << bit) != 0) { counts[bit / 7][bit % 7][piece_idx] += 1; }
+        }
+    }
+    counts
+}
+
+/// 指定された月日の全解について、マスごとに各ピースがそのマスを覆っている解の個数を7x7x8の配列で返す
+/// 個数を解の総数で割れば「このマスにどのピースが来やすいか」の確率の地図になる
+#[wasm_bindgen]
+pub fn cell_piece_frequencies(month: u32, day: u32) -> Result<JsValue, JsValue> {
+    console_error_panic_hook::set_once();
+    validate_date(month, day)?;
+
+    let raw_solutions = find_raw_solutions(month, day, compute_piece_placements());
+    Ok(serde_wasm_bindgen::to_value(&cell_piece_counts(&raw_solutions))?)
+}
+
+/// 解の盤面で、日付の2マスに上下左右で隣接するピースの番号（1〜8）を昇順で返す
+fn pieces_adjacent_to_date(board: &[Vec<i8>], month: u32, day: u32) -> Vec<i8> {
+    let mut piece_ids: Vec<i8> = [month_cell(month), day_cell(day)].iter()
+        .flat_map(|&(r, c)| [(r as i32 - 1, c as i32), (r as i32 + 1, c as i32), (r as i32, c as i32 - 1), (r as i32, c as i32 + 1)])
+        .filter(|&(nr, nc)| (0..7).contains(&nr) && (0..7).contains(&nc))
+        .map(|(nr, nc)| board[nr as usize][nc as usize])
+        .filter(|&cell| cell > 0)
+        .collect();
+    piece_ids.sort_unstable();
+    piece_ids.dedup();
+    piece_ids
+}
+
+/// 全解を通して、日付の2マスを囲むピースの組み合わせ（ピース番号の昇順リスト）を重複なく返す
+/// 結果は組み合わせの辞書順に並ぶ
+#[wasm_bindgen]
+pub fn pieces_touching_date(month: u32, day: u32) -> Result<JsValue, JsValue> {
+    console_error_panic_hook::set_once();
+    validate_date(month, day)?;
+
+    Ok(serde_wasm_bindgen::to_value(&date_neighbor_piece_sets(month, day))?)
+}
+
+/// 指定された月日の全解を通して、日付の2マスを囲むピースの組み合わせを重複なく辞書順に求める
+fn date_neighbor_piece_sets(month: u32, day: u32) -> Vec<Vec<i8>> {
+    let mut piece_sets: Vec<Vec<i8>> = solutions_for_date(month, day).iter()
+        .map(|solution| pieces_adjacent_to_date(&solution.board, month, day))
+        .collect();
+    piece_sets.sort();
+    piece_sets.dedup();
+    piece_sets
+}
+
+/// 全解を通して、日付の2マスに隣接する回数（隣接している解の個数）が最も多いピースの番号を返す - 「今日の顔」となるピース
+/// 回数が同じ場合は番号の小さいピースを採用し、解が1つもない日付では0を返す
+#[wasm_bindgen]
+pub fn signature_piece(month: u32, day: u32) -> Result<i8, JsValue> {
+    console_error_panic_hook::set_once();
+    validate_date(month, day)?;
+
+    let mut adjacency_counts = [0u32; 8];
+    for solution in solutions_for_date(month, day) {
+        for piece_id in pieces_adjacent_to_date(&solution.board, month, day) {
+            adjacency_counts[(piece_id - 1) as usize] += 1;
+        }
+    }
+    Ok(adjacency_counts.iter().enumerate()
+        .filter(|&(_, &count)| count > 0)
+        .max_by_key(|&(piece_idx, &count)| (count, std::cmp::Reverse(piece_idx)))
+        .map_or(0, |(piece_idx, _)| piece_idx as i8 + 1))
+}
+
+/// 解を、マス`cell`を覆っているピースの番号ごとに分類する - 戻り値はピース番号の昇順で、各グループ内は探索順
+/// `cell`が盤面の外か、固定の穴・日付の穴の場合はエラーメッセージを返す
+fn group_solutions_by_piece_at(month: u32, day: u32, cell: [usize; 2]) -> Result<Vec<(i8, Vec<Solution>)>, String> {
+    let [r, c] = cell;
+    if r >= 7 || c >= 7 { return Err(format!("マス({}, {})は盤面の外です", r, c)); }
+    if initial_board(month, day).get(r, c) { return Err(format!("マス({}, {})は穴です", r, c)); }
+
+    let mut groups: Vec<(i8, Vec<Solution>)> = Vec::new();
+    for solution in solutions_for_date(month, day) {
+        let piece_id = solution.board[r][c];
+        match groups.iter_mut().find(|(id, _)| *id == piece_id) {
+            Some((_, group)) => group.push(solution),
+            None => groups.push((piece_id, vec![solution])),
+        }
+    }
+    groups.sort_by_key(|&(piece_id, _)| piece_id);
+    Ok(groups)
+}
+
+/// 指定された月日の全解を、マス [行, 列] を覆っているピースの番号ごとに分類して [ピース番号, 解のリスト] のリストで返す
+/// 「このマスに何が来るか」で解を絞り込む、対話的な探索に使う
+#[wasm_bindgen]
+pub fn group_by_piece_at(month: u32, day: u32, cell: JsValue) -> Result<JsValue, JsValue> {
+    console_error_panic_hook::set_once();
+    validate_date(month, day)?;
+
+    let cell: [usize; 2] = serde_wasm_bindgen::from_value(cell)?;
+    let groups = group_solutions_by_piece_at(month, day, cell).map_err(|e| JsValue::from_str(&e))?;
+    Ok(serde_wasm_bindgen::to_value(&groups)?)
+}
+
+/// 指定された月日の全解を、盤面の中心 (3, 3) を覆っているピースの番号ごとに分類し、ピース番号から解のリストへの`Map`で返す
+/// 中心が日付の穴になる日付（11日）では、分類できないので`null`を返す
+#[wasm_bindgen]
+pub fn solve_grouped_by_center(month: u32, day: u32) -> Result<JsValue, JsValue> {
+    console_error_panic_hook::set_once();
+    validate_date(month, day)?;
+
+    if initial_board(month, day).get(3, 3) { return Ok(JsValue::NULL); }
+    let groups: BTreeMap<i8, Vec<Solution>> = group_solutions_by_piece_at(month, day, [3, 3])
+        .map_err(|e| JsValue::from_str(&e))?
+        .into_iter()
+        .collect();
+    Ok(serde_wasm_bindgen::to_value(&groups)?)
+}
+
+// --- SVG描画 ---
+
+/// 月のマスに印字されているラベル
+const MONTH_LABELS: [&str; 12] = ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+
+/// マス (行, 列) に印字されているラベルを返す - 固定の穴には`None`
+fn cell_label(r: usize, c: usize) -> Option<String> {
+    if FIXED_HOLES.contains(&(r, c)) { return None; }
+    if r < 2 { return Some(MONTH_LABELS[r * 6 + c].to_string()); }
+    Some(((r - 2) * 7 + c + 1).to_string())
+}
+
+/// 日付選択画面用に、何も置かれていない盤面のSVGを返す
+/// 固定の穴は塗りつぶし、月のマスには Jan〜Dec、日のマスには 1〜31 のラベルを描く
+/// `cell_px`は1マスの大きさ（ピクセル）
+#[wasm_bindgen]
+pub fn empty_board_svg(variant: &str, cell_px: u32) -> Result<String, JsValue> {
+    console_error_panic_hook::set_once();
+    check_variant(variant)?;
+
+    let size = cell_px * 7;
+    let mut svg = format!(r#"<svg xmlns="http://www.w3.org/2000/svg" width="{size}" height="{size}" viewBox="0 0 {size} {size}">"#);
+    for r in 0..7 {
+        for c in 0..7 {
+            let (x, y) = (c as u32 * cell_px, r as u32 * cell_px);
+            match cell_label(r, c) {
+                None => svg.push_str(&format!(r##"<rect x="{x}" y="{y}" width="{cell_px}" height="{cell_px}" fill="#444"/>"##)),
+                Some(label) => {
+                    svg.push_str(&format!(r##"<rect x="{x}" y="{y}" width="{cell_px}" height="{cell_px}" fill="#fff" stroke="#444"/>"##));
+                    let (cx, cy) = (x + cell_px / 2, y + cell_px / 2);
+                    let font_size = cell_px / 3;
+                    svg.push_str(&format!(r#"<text x="{cx}" y="{cy}" font-size="{font_size}" text-anchor="middle" dominant-baseline="central">{label}</text>"#));
+                }
+            }
+        }
+    }
+    svg.push_str("</svg>");
+    Ok(svg)
+}
+
+/// マスの集合（bit (行 * 7 + 列)）の外周を、格子点 (x, y) = (列, 行) を時計回りに結んだ閉路のリストにする
+/// 各マスの辺のうち隣が集合に含まれない辺を向き付きで集め、終点から始まる辺をたどって閉路につなぐ
+/// 同じ向きに続く辺は1本にまとめるので、閉路には角の点だけが残る
+fn trace_outline(cells: u64) -> Vec<Vec<(usize, usize)>> {
+    let contains = |r: isize, c: isize| (0..7).contains(&r) && (0..7).contains(&c) && (cells >> (r * 7 + c)) & 1 == 1;
+    let mut edges: HashMap<(usize, usize), Vec<(usize, usize)>> = HashMap::new();
+    for i in (0..49).filter(|&i| (cells >> i) & 1 == 1) {
+        let (r, c) = (i / 7, i % 7);
+        let (ri, ci) = (r as isize, c as isize);
+        if !contains(ri - 1, ci) { edges.entry((c, r)).or_default().push((c + 1, r)); }
+        if !contains(ri, ci + 1) { edges.entry((c + 1, r)).or_default().push((c + 1, r + 1)); }
+        if !contains(ri + 1, ci) { edges.entry((c + 1, r + 1)).or_default().push((c, r + 1)); }
+        if !contains(ri, ci - 1) { edges.entry((c, r + 1)).or_default().push((c, r)); }
+    }
+
+    let mut loops = Vec::new();
+    let mut starts: Vec<(usize, usize)> = edges.keys().copied().collect();
+    starts.sort_unstable();
+    for start in starts {
+        while edges.get(&start).is_some_and(|targets| !targets.is_empty()) {
+            let mut points = vec![start];
+            let mut current = start;
+            while let Some(next) = edges.get_mut(&current).and_then(|targets| targets.pop()) {
+                if next == start { break; }
+                points.push(next);
+                current = next;
+            }
+            // 一直線に並んだ途中の点を取り除く
+            let n = points.len();
+            let corners = (0..n).filter(|&k| {
+                let (prev, point, next) = (points[(k + n - 1) % n], points[k], points[(k + 1) % n]);
+                (point.0 as isize - prev.0 as isize, point.1 as isize - prev.1 as isize) != (next.0 as isize - point.0 as isize, next.1 as isize - point.1 as isize)
+            }).map(|k| points[k]).collect();
+            loops.push(corners);
+        }
+    }
+    loops
+}
+
+/// 7x7の盤面をSVGの文字列にする - 各ピースは外周をなぞった1つの`<path>`、日付の穴は濃い灰色の`<rect>`で描く
+/// 背景（固定の穴）はフロントエンドの盤面と同じ薄い灰色で、色は`static/index.js`と同じ`PIECE_COLORS`を使う
+fn board_to_svg(board: &[Vec<i8>], cell_size: u32) -> Result<String, String> {
+    if board.len() != 7 || board.iter().any(|row| row.len() != 7) {
+        return Err("盤面は7x7である必要があります".to_string());
+    }
+    if let Some(&cell) = board.iter().flatten().find(|&&cell| !(-1..=8).contains(&cell)) {
+        return Err(format!("盤面の値は-1〜8である必要があります: {}", cell));
+    }
+    let size = cell_size as usize;
+    let side = 7 * size;
+    let mut svg = format!(r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 {0} {0}" width="{0}" height="{0}">"#, side);
+    svg.push_str(&format!(r##"<rect x="0" y="0" width="{0}" height="{0}" fill="#DDDDDD"/>"##, side));
+
+    for (r, row) in board.iter().enumerate() {
+        for (c, &cell) in row.iter().enumerate() {
+            if cell == -1 {
+                svg.push_str(&format!(r##"<rect class="date-hole" x="{}" y="{}" width="{2}" height="{2}" fill="#333333"/>"##, c * size, r * size, size));
+            }
+        }
+    }
+    for (piece_id, &(_, hex)) in (1..).zip(PIECE_COLORS.iter()) {
+        let cells = board.iter().flatten().enumerate()
+            .filter(|&(_, &cell)| cell == piece_id)
+            .fold(0u64, |mask, (i, _)| mask | (1u64 << i));
+        if cells == 0 { continue; }
+        let d: Vec<String> = trace_outline(cells).iter().map(|points| {
+            let path: Vec<String> = points.iter().map(|&(x, y)| format!("{} {}", x * size, y * size)).collect();
+            format!("M {} Z", path.join(" L "))
+        }).collect();
+        svg.push_str(&format!(r##"<path data-piece="{}" d="{}" fill="{}" fill-rule="evenodd" stroke="#555555"/>"##, piece_id, d.join(" "), hex));
+    }
+    svg.push_str("</svg>");
+    Ok(svg)
+}
+
+/// 解の盤面（`Solution`の`board`と同じ形式）を、そのままDOMに挿入できる`<svg>`の文字列にして返す
+/// 1マスの大きさは`cell_size`ピクセルで、`viewBox`は7マス分の正方形になる
+/// 各ピースはマスを結合した外形の`<path>`1つ（`data-piece`にピースのID）で、`piece_colors`と同じ色で塗る
+#[wasm_bindgen]
+pub fn solution_to_svg(board: JsValue, cell_size: u32) -> Result<String, JsValue> {
+    console_error_panic_hook::set_once();
+
+    let board: Vec<Vec<i8>> = serde_wasm_bindgen::from_value(board)?;
+    board_to_svg(&board, cell_size).map_err(|e| JsValue::from_str(&e))
+}
+
+// --- 探索オプション ---
+
+/// `solve_for_date_with_config`に渡す探索オプション - 省略したフィールドはデフォルト値になる
+#[derive(Serialize, Deserialize, Default, Clone, Copy)]
+#[serde(default)]
+struct SolveConfig {
+    /// trueなら埋まっているマスと空きマスを入れ替えた「裏返し」パズルを解く
+    /// 日付の穴と固定の穴をピースで覆い、それ以外のマスを空けておく - 標準のピース集合では埋められないので、`run_scenario`で独自のピース集合と組み合わせて使う
+    invert_holes: bool,
+    /// 返す解の並べ順
+    sort_by: SortKey,
+}
+
+/// 解の並べ順
+#[derive(Serialize, Deserialize, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+enum SortKey {
+    /// 探索で見つかった順
+    #[default]
+    Search,
+    /// ピースの重心が盤面の中心から均等な距離に散らばっている解ほど先に並べる（`balance_score`の昇順）
+    Balance,
+}
+
+/// 各ピースの重心と盤面の中心 (3, 3) との距離の分散 - 小さいほどピースが中心の周りに満遍なく配置されている
+/// 盤面に現れないピースは計算に含めない
+fn balance_score(board: &[Vec<i8>]) -> f64 {
+    let mut sums = [(0.0f64, 0.0f64, 0u32); 8];
+    for (r, row) in board.iter().enumerate() {
+        for (c, &cell) in row.iter().enumerate() {
+            if (1..=8).contains(&cell) {
+                let sum = &mut sums[(cell - 1) as usize];
+                *sum = (sum.0 + r as f64, sum.1 + c as f64, sum.2 + 1);
+            }
+        }
+    }
+    let distances: Vec<f64> = sums.iter()
+        .filter(|&&(_, _, n)| n > 0)
+        .map(|&(sr, sc, n)| (sr / n as f64 - 3.0).hypot(sc / n as f64 - 3.0))
+        .collect();
+    if distances.is_empty() { return 0.0; }
+    let mean = distances.iter().sum::<f64>() / distances.len() as f64;
+    distances.iter().map(|d| (d - mean).powi(2)).sum::<f64>() / distances.len() as f64
+}
+
+/// 並べ順に従って解を並べ替える - 同じスコアの解は探索順を保つ
+fn sort_solutions(solutions: &mut [Solution], sort_by: SortKey) {
+    if sort_by == SortKey::Balance {
+        solutions.sort_by(|a, b| balance_score(&a.board).total_cmp(&balance_score(&b.board)));
+    }
+}
+
+/// 探索オプション付きで、指定された月日の全解を求める
+/// 裏返した盤面の空きマスは固定の穴と日付の穴の8個だけで、標準のピース集合（合計41マス）ではちょうど埋められないので、
+/// `invert_holes`が指定された場合は探索せずにエラーメッセージを返す - 裏返しパズルは`run_scenario`で空きマスに合ったピース集合を指定して解く
+fn solutions_for_config(month: u32, day: u32, config: &SolveConfig) -> Result<Vec<Solution>, String> {
+    if config.invert_holes {
+        return Err("invert_holesは標準のピース集合では使えません - 空きマスに合ったピース集合をrun_scenarioで指定してください".to_string());
+    }
+
+    let mut solutions = solutions_for_date(month, day);
+    sort_solutions(&mut solutions, config.sort_by);
+    Ok(solutions)
+}
+
+/// 探索オプション `{ invert_holes: bool, sort_by: "search" | "balance" }` を指定して、指定された月日の全解を返す
+/// 標準のピース集合では裏返した盤面の空きマス（8個）がピースの合計面積と釣り合わないため、`invert_holes`にtrueを指定するとエラーになる
+#[wasm_bindgen]
+pub fn solve_for_date_with_config(month: u32, day: u32, config: JsValue) -> Result<JsValue, JsValue> {
+    console_error_panic_hook::set_once();
+    validate_date(month, day)?;
+
+    let config: SolveConfig = serde_wasm_bindgen::from_value(config)?;
+    let solutions = solutions_for_config(month, day, &config).map_err(|e| JsValue::from_str(&e))?;
+    Ok(serde_wasm_bindgen::to_value(&solutions)?)
+}
+
+// --- 途中まで置いた状態からの解の数 ---
+
+/// 盤面`start_board`に、先頭k個のピースを`pinned`の配置で固定したときの、残りのピースの置き方の数を数える
+/// 固定したピースどうし・穴と重なっている場合は0を返す
+fn count_after_pinning(start_board: Board, pinned: &[u64], all_piece_placements: &[Vec<u64>]) -> u32 {
+    let mut board = start_board;
+    let mut pinned_placements = [0; 8];
+    for (piece_idx, &mask) in pinned.iter().enumerate() {
+        if board.overlaps(&Board(mask)) { return 0; }
+        board = board.union(&Board(mask));
+        pinned_placements[piece_idx] = mask;
+    }
+
+    let mut count = 0;
+    run_search_from(board, pinned_placements, all_piece_placements, &mut |_: &[u64; 8]| {
+        count += 1;
+        true
+    });
+    count
+}
+
+/// 解のピースを1から順に固定していったとき、各時点で残っている解の数を返す
+/// 要素kは、ピース1〜k+1を解の通りに置いた後で、それと矛盾しない完成形の数
+/// 正しい解なら単調非増加で、最後は必ず1（その解自身）になる
+#[wasm_bindgen]
+pub fn solution_count_trajectory(solution: JsValue) -> Result<JsValue, JsValue> {
+    console_error_panic_hook::set_once();
+
+    let solution: Solution = serde_wasm_bindgen::from_value(solution)?;
+    if solution.board.len() != 7 || solution.board.iter().any(|row| row.len() != 7) {
+        return Err(JsValue::from_str("盤面は7x7である必要があります"));
+    }
+
+    Ok(serde_wasm_bindgen::to_value(&count_trajectory(&solution))?)
+}
+
+/// 解のピースを1から順に固定していったときの、各時点で残っている解の数
+fn count_trajectory(solution: &Solution) -> Vec<u32> {
+    let all_piece_placements = compute_piece_placements();
+    let start_board = solution_hole_board(solution);
+    let piece_masks = solution_piece_masks(solution);
+    (1..=8).map(|k| count_after_pinning(start_board, &piece_masks[..k], all_piece_placements)).collect()
+}
+
+// --- ピースを固定した探索 ---
+
+/// 探索の前に位置を固定しておくピース
+#[derive(Deserialize)]
+struct LockedPiece {
+    /// ピースのID(1-8)
+    piece_id: u8,
+    /// ピースが埋めるマスの外接長方形の左上を置くマスの行
+    row: usize,
+    /// ピースが埋めるマスの外接長方形の左上を置くマスの列
+    col: usize,
+    /// `get_unique_rotations`の何番目の向きか（0が基本形）
+    rotation: usize,
+}
+
+/// 固定するピースを配置ビットマスクにする - 盤面からはみ出す指定はエラーメッセージを返す
+fn locked_piece_mask(locked: &LockedPiece) -> Result<u64, String> {
+    if !(1..=8).contains(&locked.piece_id) { return Err(format!("ピースのIDは1〜8で指定してください: {}", locked.piece_id)); }
+    let rotations = get_unique_rotations(&get_initial_pieces()[(locked.piece_id - 1) as usize]);
+    let shape = rotations.get(locked.rotation)
+        .ok_or_else(|| format!("ピース{}の向きは0〜{}で指定してください: {}", locked.piece_id, rotations.len() - 1, locked.rotation))?;
+
+    // `get_unique_rotations`の形状は埋まっているマスの外接長方形に切り詰めてあるので、その左上を(row, col)に合わせる
+    let mut piece = Board::default();
+    for (i, row) in shape.iter().enumerate() {
+        for (j, &cell) in row.iter().enumerate() {
+            if cell != 1 { continue; }
+            let (r, c) = (locked.row + i, locked.col + j);
+            if r >= 7 || c >= 7 { return Err(format!("ピース{}が盤面からはみ出しています", locked.piece_id)); }
+            piece.set(r, c);
+        }
+    }
+    Ok(piece.0)
+}
+
+/// 一部のピースを固定した上で、指定された月日の残りのピースの置き方を全て求める
+/// 固定したピースのマスは最初から埋めておき、残りのピースだけで空きマスを埋める
+/// 固定したピースが穴や他の固定ピースと重なる場合、同じピースを2回固定した場合はエラーメッセージを返す
+fn solutions_with_locked(month: u32, day: u32, locked: &[LockedPiece]) -> Result<Vec<Solution>, String> {
+    let all_piece_placements = compute_piece_placements();
+    let mut start_board = initial_board(month, day);
+    let mut locked_masks = [0; 8];
+    for piece in locked {
+        let mask = locked_piece_mask(piece)?;
+        let piece_idx = (piece.piece_id - 1) as usize;
+        if locked_masks[piece_idx] != 0 { return Err(format!("ピース{}が2回固定されています", piece.piece_id)); }
+        if start_board.overlaps(&Board(mask)) { return Err(format!("ピース{}が穴か他の固定ピースと重なっています", piece.piece_id)); }
+        start_board = start_board.union(&Board(mask));
+        locked_masks[piece_idx] = mask;
+    }
+
+    let mut solutions = Vec::new();
+    run_search_from(start_board, locked_masks, all_piece_placements, &mut |masks: &[u64; 8]| {
+        solutions.push(masks_to_solution(masks, month, day));
+        true
+    });
+    Ok(solutions)
+}
+
+/// ピースを `[{ piece_id, row, col, rotation }, ...]` の位置に固定した上で、指定された月日の解を全て返す
+/// 「ピース3がここにあるとき、残りはどう埋まるか」を見せるチュートリアル向け
+#[wasm_bindgen]
+pub fn solve_with_locked(month: u32, day: u32, locked: JsValue) -> Result<JsValue, JsValue> {
+    console_error_panic_hook::set_once();
+    validate_date(month, day)?;
+
+    let locked: Vec<LockedPiece> = serde_wasm_bindgen::from_value(locked)?;
+    let solutions = solutions_with_locked(month, day, &locked).map_err(|e| JsValue::from_str(&e))?;
+    Ok(serde_wasm_bindgen::to_value(&solutions)?)
+}
+
+// --- 複数日付の一括探索 ---
+
+/// ある日付と、その日付の全解
+#[derive(Serialize)]
+struct DateSolutions {
+    month: u32,
+    day: u32,
+    solutions: Vec<Solution>,
+}
+
+/// 指定された日付のリスト `[[月, 日], ...]` それぞれの全解を、`{ month, day, solutions }`のリストで返す
+/// 配置パターンの事前計算は1回だけ行い、全日付で共有する
+/// 存在しない日付が含まれている場合はエラーを返す
+#[wasm_bindgen]
+pub fn solve_dates(dates: JsValue) -> Result<JsValue, JsValue> {
+    console_error_panic_hook::set_once();
+
+    let dates: Vec<(u32, u32)> = serde_wasm_bindgen::from_value(dates)?;
+    for &(month, day) in &dates { validate_date(month, day)?; }
+
+    Ok(serde_wasm_bindgen::to_value(&solutions_for_dates(&dates))?)
+}
+
+/// 日付のリストそれぞれの全解を求める - 配置パターンは全日付で共有する
+fn solutions_for_dates(dates: &[(u32, u32)]) -> Vec<DateSolutions> {
+    let all_piece_placements = compute_piece_placements();
+    dates.iter().map(|&(month, day)| {
+        let solutions = find_raw_solutions(month, day, all_piece_placements)
+            .iter().map(|masks| masks_to_solution(masks, month, day)).collect();
+        DateSolutions { month, day, solutions }
+    }).collect()
+}
+
+// --- 再現用シナリオ ---
+
+/// シナリオ形式のバージョン - 形式を変えたら上げる
+const SCENARIO_VERSION: u32 = 1;
+
+/// 不具合報告や回帰テスト用に、探索条件を丸ごと記録したシナリオ
+#[derive(Serialize, Deserialize)]
+struct Scenario {
+    /// シナリオ形式のバージョン
+    version: u32,
+    /// 盤面のバリエーション名
+    variant: String,
+    month: u32,
+    day: u32,
+    /// 使うピースの形状（`get_initial_pieces`と同じ形式）
+    pieces: Vec<Vec<Vec<u8>>>,
+    /// 探索開始時に埋まっているマスを1とした7x7の盤面（固定の穴と日付の穴）
+    holes: Vec<Vec<u8>>,
+    /// 探索オプション
+    config: SolveConfig,
+}
+
+/// シナリオを実行した結果
+#[derive(Serialize)]
+struct SolveResult {
+    count: u32,
+    solutions: Vec<Solution>,
+}
+
+/// シナリオに記録された条件で探索する - 返す盤面では、最初から埋まっていたマスを全て-1で表す
+fn run_scenario_internal(scenario: &Scenario) -> Result<SolveResult, String> {
+    if scenario.version != SCENARIO_VERSION {
+        return Err(format!("未対応のシナリオのバージョンです: {}", scenario.version));
+    }
+    if scenario.holes.len() != 7 || scenario.holes.iter().any(|row| row.len() != 7) {
+        return Err("盤面は7x7である必要があります".to_string());
+    }
+
+    let mut start_board = Board::from_grid(&scenario.holes);
+    if scenario.config.invert_holes { start_board = Board(FULL_BOARD_MASK & !start_board.0); }
+    let piece_counts: Vec<(Vec<Vec<u8>>, u32)> = scenario.pieces.iter().map(|shape| (shape.clone(), 1)).collect();
+    let custom_pieces = build_custom_pieces(&piece_counts)?;
+    check_custom_area(&custom_pieces, start_board)?;
+
+    let mut found_raw_solutions = Vec::new();
+    find_solutions_custom(0, start_board, &mut Vec::new(), &custom_pieces, 7, &mut found_raw_solutions);
+    let mut solutions: Vec<Solution> = found_raw_solutions.iter().map(|masks| {
+        let masks: Vec<Option<u64>> = masks.iter().map(|&mask| Some(mask)).collect();
+        masks_to_solution_with_blocked(&masks, start_board, &scenario.pieces)
+    }).collect();
+    sort_solutions(&mut solutions, scenario.config.sort_by);
+    Ok(SolveResult { count: solutions.len() as u32, solutions })
+}
+
+/// 探索オプションと月日から、バリエーション・ピース集合・穴・オプションを記録したシナリオのJSON文字列を作る
+#[wasm_bindgen]
+pub fn export_scenario(config: JsValue, month: u32, day: u32) -> Result<String, JsValue> {
+    console_error_panic_hook::set_once();
+    validate_date(month, day)?;
+
+    let config: SolveConfig = serde_wasm_bindgen::from_value(config)?;
+    let json = js_sys::JSON::stringify(&serde_wasm_bindgen::to_value(&scenario_for_date(month, day, config))?)?;
+    Ok(json.into())
+}
+
+/// 標準の盤面とピース集合で、指定された月日を探索オプション`config`で解くシナリオを作る
+fn scenario_for_date(month: u32, day: u32, config: SolveConfig) -> Scenario {
+    Scenario {
+        version: SCENARIO_VERSION,
+        variant: "dragon_fjord".to_string(),
+        month,
+        day,
+        pieces: get_initial_pieces(),
+        holes: initial_board(month, day).to_grid(),
+        config,
+    }
+}
+
+/// `export_scenario`で作ったシナリオのJSON文字列を読み込んで探索し、`{ count, solutions }`を返す
+#[wasm_bindgen]
+pub fn run_scenario(json: &str) -> Result<JsValue, JsValue> {
+    console_error_panic_hook::set_once();
+
+    let scenario: Scenario = serde_wasm_bindgen::from_value(js_sys::JSON::parse(json)?)?;
+    let result = run_scenario_internal(&scenario).map_err(|e| JsValue::from_str(&e))?;
+    Ok(serde_wasm_bindgen::to_value(&result)?)
+}
+
+/// カレンダー全体で、出現回数が最も少ない配置パターンをk個、[ピース番号(0-7), 配置ビットマスク, 出現回数] のリストで返す
+/// 出現回数は全日付の全解で、その配置が使われた回数の合計 - 一度も使われない配置は含めない
+/// 出現回数の昇順に並べ、同数の場合はピース番号・ビットマスクの昇順とする
+#[wasm_bindgen]
+pub fn rarest_placements(variant: &str, k: usize) -> Result<JsValue, JsValue> {
+    console_error_panic_hook::set_once();
+    check_variant(variant)?;
+
+    let all_piece_placements = compute_piece_placements();
+    let raw_solution_sets = all_dates().map(|(month, day)| find_raw_solutions(month, day, all_piece_placements));
+    Ok(serde_wasm_bindgen::to_value(&rarest_placements_in(raw_solution_sets, k))?)
+}
+
+/// 日付ごとの解のリスト`raw_solution_sets`を通して、出現回数が最も少ない配置パターンをk個、(ピース番号, 配置ビットマスク, 出現回数) で求める
+/// 日付ごとの解は集計したらすぐに捨てるので、全日付の解を同時にメモリに置かずに済む
+fn rarest_placements_in(raw_solution_sets: impl IntoIterator<Item = Vec<[u64; 8]>>, k: usize) -> Vec<(usize, u64, u32)> {
+    let mut totals: Vec<HashMap<u64, u32>> = vec![HashMap::new(); 8];
+    for raw_solutions in raw_solution_sets {
+        for (piece_idx, frequencies) in placement_frequencies(&raw_solutions).into_iter().enumerate() {
+            for (mask, count) in frequencies { *totals[piece_idx].entry(mask).or_insert(0) += count; }
+        }
+    }
+
+    let mut rarest: Vec<(usize, u64, u32)> = totals.iter().enumerate()
+        .flat_map(|(piece_idx, frequencies)| frequencies.iter().map(move |(&mask, &count)| (piece_idx, mask, count)))
+        .collect();
+    rarest.sort_unstable_by_key(|&(piece_idx, mask, count)| (count, piece_idx, mask));
+    rarest.truncate(k);
+    rarest
+}
+
+// --- ランレングス符号化 ---
+
+/// 盤面のマスの値を1文字に変換する - ピース1〜8はA〜H、日付の穴(-1)は`.`、固定の穴(0)は`#`
+fn cell_to_symbol(cell: i8) -> char {
+    match cell {
+        1..=8 => (b'A' + (cell - 1) as u8) as char,
+        -1 => '.',
+        _ => '#',
+    }
+}
+
+/// `cell_to_symbol`の逆変換
+fn symbol_to_cell(symbol: char) -> Option<i8> {
+    match symbol {
+        'A'..='H' => Some((symbol as u8 - b'A') as i8 + 1),
+        '.' => Some(-1),
+        '#' => Some(0),
+        _ => None,
+    }
+}
+
+/// 盤面を行優先でランレングス符号化する - 「個数+記号」の並びで、連続は行をまたいでもよい
+/// 1マスだけの連続は個数を省いて記号だけを書く - 省かないと1マスの連続が多い分だけ49文字より長くなるが、省けば全ての解が49文字未満になる
+/// 例: `3A2B.`はA(ピース1)が3マス、B(ピース2)が2マス、日付の穴が1マス続くことを表す
+fn encode_board_rle(board: &[Vec<i8>]) -> String {
+    let mut encoded = String::new();
+    let mut cells = board.iter().flatten().peekable();
+    while let Some(&cell) = cells.next() {
+        let mut run = 1;
+        while cells.next_if(|&&next| next == cell).is_some() { run += 1; }
+        if run > 1 { encoded.push_str(&run.to_string()); }
+        encoded.push(cell_to_symbol(cell));
+    }
+    encoded
+}
+
+/// `encode_board_rle`で符号化した文字列を7x7の盤面に戻す - 個数のない記号は1マス分として読む
+fn decode_board_rle(encoded: &str) -> Result<Vec<Vec<i8>>, String> {
+    let mut cells = Vec::new();
+    let mut run = 0usize;
+    for ch in encoded.chars() {
+        if let Some(digit) = ch.to_digit(10) {
+            // 49を超えた個数はどのみち盤面に収まらないので、桁が続いてもあふれる前に打ち切る
+            run = run * 10 + digit as usize;
+            if run > 49 { return Err("マスの数が49を超えています".to_string()); }
+        } else {
+            let cell = symbol_to_cell(ch).ok_or_else(|| format!("不正な記号です: {}", ch))?;
+            if run == 0 { run = 1; }
+            if cells.len() + run > 49 { return Err("マスの数が49を超えています".to_string()); }
+            cells.extend(std::iter::repeat_n(cell, run));
+            run = 0;
+        }
+    }
+    if run != 0 || cells.len() != 49 { return Err("盤面は7x7である必要があります".to_string()); }
+    Ok(cells.chunks(7).map(|row| row.to_vec()).collect())
+}
+
+/// 指定された月日の全解を、ランレングス符号化した盤面の文字列のリストで返す
+/// ピースのマスは連続して並びやすいため、49文字をそのまま並べるよりも短くなる
+#[wasm_bindgen]
+pub fn solutions_rle(month: u32, day: u32) -> Result<JsValue, JsValue> {
+    console_error_panic_hook::set_once();
+    validate_date(month, day)?;
+
+    let encoded: Vec<String> = solutions_for_date(month, day).iter().map(|solution| encode_board_rle(&solution.board)).collect();
+    Ok(serde_wasm_bindgen::to_value(&encoded)?)
+}
+
+/// `solutions_rle`が返した文字列を7x7の盤面に戻す
+#[wasm_bindgen]
+pub fn decode_rle(encoded: &str) -> Result<JsValue, JsValue> {
+    console_error_panic_hook::set_once();
+
+    let board = decode_board_rle(encoded).map_err(|e| JsValue::from_str(&e))?;
+    Ok(serde_wasm_bindgen::to_value(&board)?)
+}
+
+// --- 解の集合の圧縮 ---
+
+/// `encode_solution_set`の形式の版番号 - 形式を変えたら番号を上げ、古い文字列を誤って読まないようにする
+const SOLUTION_SET_FORMAT_VERSION: u8 = 1;
+
+/// 配置ビットマスク1個あたりのバイト数 - 49マスなので7バイトに収まる
+const MASK_BYTES: usize = 7;
+
+/// URLセーフなbase64（RFC 4648 の5節）の文字
+const BASE64_ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+/// バイト列をURLセーフなbase64（`=`による埋め草なし）の文字列にする
+fn encode_base64(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let bits = chunk.iter().enumerate().fold(0u32, |bits, (i, &byte)| bits | (byte as u32) << (16 - 8 * i));
+        for i in 0..=chunk.len() { encoded.push(BASE64_ALPHABET[(bits >> (18 - 6 * i)) as usize & 63] as char); }
+    }
+    encoded
+}
+
+/// `encode_base64`の文字列をバイト列に戻す - 不正な文字や長さの場合はエラーメッセージを返す
+fn decode_base64(encoded: &str) -> Result<Vec<u8>, String> {
+    if encoded.len() % 4 == 1 { return Err("base64の文字列の長さが不正です".to_string()); }
+    let mut bytes = Vec::with_capacity(encoded.len() * 3 / 4);
+    for chunk in encoded.as_bytes().chunks(4) {
+        let mut bits = 0u32;
+        for (i, &ch) in chunk.iter().enumerate() {
+            let value = BASE64_ALPHABET.iter().position(|&c| c == ch).ok_or_else(|| format!("base64に使えない文字です: {}", ch as char))?;
+            bits |= (value as u32) << (18 - 6 * i);
+        }
+        bytes.extend((0..chunk.len() - 1).map(|i| (bits >> (16 - 8 * i)) as u8));
+    }
+    Ok(bytes)
+}
+
+/// 解の集合（ピースごとの配置ビットマスクの配列のリスト）を文字列にする
+/// 先頭の1バイトが版番号で、その後に各解の8個の配置ビットマスクを7バイトずつ（リトルエンディアン）並べ、base64で符号化する
+fn encode_solution_set(raw_solutions: &[[u64; 8]]) -> String {
+    let mut bytes = vec![SOLUTION_SET_FORMAT_VERSION];
+    for &mask in raw_solutions.iter().flatten() { bytes.extend_from_slice(&mask.to_le_bytes()[..MASK_BYTES]); }
+    encode_base64(&bytes)
+}
+
+/// `encode_solution_set`の文字列を、指定された月日の解の集合として読み戻す
+/// 版番号が違う場合、各解が指定された月日の盤面を重なりなく埋めていない場合、
+/// ピースの配置がそのピースの配置パターン（`compute_piece_placements`）にない場合はエラーメッセージを返す
+fn decode_solution_set(month: u32, day: u32, encoded: &str) -> Result<Vec<[u64; 8]>, String> {
+    let bytes = decode_base64(encoded)?;
+    let (&version, body) = bytes.split_first().ok_or("版番号がありません")?;
+    if version != SOLUTION_SET_FORMAT_VERSION { return Err(format!("未対応の形式の版です: {}", version)); }
+    if body.len() % (8 * MASK_BYTES) != 0 { return Err("解のデータの長さが不正です".to_string()); }
+
+    let all_piece_placements = compute_piece_placements();
+    let empty_cells = !initial_board(month, day).0 & FULL_BOARD_MASK;
+    body.chunks(8 * MASK_BYTES).map(|chunk| {
+        let mut masks = [0u64; 8];
+        for (mask, mask_bytes) in masks.iter_mut().zip(chunk.chunks(MASK_BYTES)) {
+            let mut le_bytes = [0u8; 8];
+            le_bytes[..MASK_BYTES].copy_from_slice(mask_bytes);
+            *mask = u64::from_le_bytes(le_bytes);
+        }
+        let covered = masks.iter().fold(0, |covered, &mask| covered | mask);
+        let cell_count: u32 = masks.iter().map(|mask| mask.count_ones()).sum();
+        if covered != empty_cells || cell_count != empty_cells.count_ones() {
+            return Err(format!("{}月{}日の解ではありません", month, day));
+        }
+        // 配置パターンは昇順に並んでいるので、二分探索で引ける
+        if let Some(piece_idx) = (0..8).find(|&piece_idx| all_piece_placements[piece_idx].binary_search(&masks[piece_idx]).is_err()) {
+            return Err(format!("ピース{}の配置がピースの形と一致しません", piece_idx + 1));
+        }
+        Ok(masks)
+    }).collect()
+}
+
+/// 指定された月日の全解を、1つの短い文字列（URLセーフなbase64）にまとめて返す
+/// `localStorage`への保存や共有用のURLに使い、`decode_solutions`で元の解のリストに戻せる
+#[wasm_bindgen]
+pub fn encode_solutions(month: u32, day: u32) -> Result<String, JsValue> {
+    console_error_panic_hook::set_once();
+    validate_date(month, day)?;
+
+    Ok(encode_solution_set(&find_raw_solutions(month, day, compute_piece_placements())))
+}
+
+/// `encode_solutions`が返した文字列を、`solve_for_date`と同じ形式の解のリストに戻す
+/// 文字列が壊れている場合や、別の日付の解だった場合はエラーを返す
+#[wasm_bindgen]
+pub fn decode_solutions(month: u32, day: u32, encoded: &str) -> Result<JsValue, JsValue> {
+    console_error_panic_hook::set_once();
+    validate_date(month, day)?;
+
+    let raw_solutions = decode_solution_set(month, day, encoded).map_err(|e| JsValue::from_str(&e))?;
+    let solutions: Vec<Solution> = raw_solutions.iter().map(|masks| masks_to_solution(masks, month, day)).collect();
+    Ok(serde_wasm_bindgen::to_value(&solutions)?)
+}
+
+// --- デバッグ用の表示 ---
+
+/// 7x7の盤面を、`cell_to_symbol`の記号を空白区切りで並べた7行の文字列にする（末尾に改行は付けない）
+fn render_board_ascii(board: &[Vec<i8>]) -> Result<String, String> {
+    if board.len() != 7 || board.iter().any(|row| row.len() != 7) {
+        return Err("盤面は7x7である必要があります".to_string());
+    }
+    let lines: Vec<String> = board.iter()
+        .map(|row| row.iter().map(|&cell| cell_to_symbol(cell).to_string()).collect::<Vec<_>>().join(" "))
+        .collect();
+    Ok(lines.join("\n"))
+}
+
+/// 解の盤面（`Solution`の`board`と同じ形式）を、UIなしで目視確認できる文字列にして返す
+/// ピース1〜8はA〜H、日付の穴は`.`、固定の穴は`#`で表す
+#[wasm_bindgen]
+pub fn render_solution_ascii(board: JsValue) -> Result<String, JsValue> {
+    console_error_panic_hook::set_once();
+
+    let board: Vec<Vec<i8>> = serde_wasm_bindgen::from_value(board)?;
+    render_board_ascii(&board).map_err(|e| JsValue::from_str(&e))
+}
+
+// --- 別アルゴリズムによる検算 ---
+
+/// バックトラッキング探索とDancing Linksで同じ日付の解の個数を数え、一致すればその個数を返す
+/// 一致しない場合は、どちらかのソルバーに不具合があるのでエラーメッセージを返す
+/// どちらも`compute_piece_placements`の配置パターンを使うので、検算できるのは探索の部分だけで、配置パターンの誤りは見つけられない
+#[cfg(feature = "dlx")]
+fn cross_check_count_internal(month: u32, day: u32) -> Result<u32, String> {
+    let all_piece_placements = compute_piece_placements();
+    let backtracking_count = count_raw_solutions(month, day, all_piece_placements);
+    let dlx_count = dlx::count_exact_covers(initial_board(month, day).0, all_piece_placements);
+    if backtracking_count != dlx_count {
+        return Err(format!("{}月{}日の解の個数が一致しません: バックトラッキング{}個、DLX{}個", month, day, backtracking_count, dlx_count));
+    }
+    Ok(backtracking_count)
+}
+
+/// 指定された月日の解の個数を、2つの異なるアルゴリズムで数えて照合した上で返す（`dlx`フィーチャーが必要）
+#[cfg(feature = "dlx")]
+#[wasm_bindgen]
+pub fn cross_check_count(month: u32, day: u32) -> Result<u32, JsValue> {
+    console_error_panic_hook::set_once();
+    validate_date(month, day)?;
+
+    cross_check_count_internal(month, day).map_err(|e| JsValue::from_str(&e))
+}
+
+/// 指定された月日の全解を、Dancing Links（Knuth の Algorithm X）で求めて返す（`dlx`フィーチャーが必要）
+/// 解の集合は`solve_for_date`と同じで、並び順だけが異なる - 2つの探索方法の速度比較に使う
+#[cfg(feature = "dlx")]
+#[wasm_bindgen]
+pub fn solve_for_date_dlx(month: u32, day: u32) -> Result<JsValue, JsValue> {
+    console_error_panic_hook::set_once();
+    validate_date(month, day)?;
+
+    let all_piece_placements = compute_piece_placements();
+    let solutions: Vec<Solution> = dlx::exact_covers(initial_board(month, day).0, all_piece_placements).iter()
+        .map(|masks| masks_to_solution(masks, month, day))
+        .collect();
+    Ok(serde_wasm_bindgen::to_value(&solutions)?)
+}
+
+// --- 曜日つきの盤面 ---
+
+/// 曜日つきの盤面の行数 - 幅は標準の盤面と同じ7列で、bit (行 * 7 + 列) がマスを表す
+/// 0〜1行目は月、2〜5行目と6行目の先頭3マスは日、6行目の残り4マスは日〜水、7行目の右3マスは木〜土
+const WEEKDAY_ROWS: usize = 8;
+
+/// 曜日つきの盤面の固定の穴（常に埋まっているマス）の座標 (行, 列)
+const WEEKDAY_FIXED_HOLES: [(usize, usize); 6] = [(0, 6), (1, 6), (7, 0), (7, 1), (7, 2), (7, 3)];
+
+/// 曜日の穴の座標 (行, 列) - `weekday`は0が日曜、6が土曜
+fn weekday_cell(weekday: u32) -> (usize, usize) {
+    if weekday < 4 { (6, 3 + weekday as usize) } else { (7, weekday as usize) }
+}
+
+/// 曜日つきの盤面で使う10個のピース - 5マスが7個と4マスが3個で、合計面積は空きマス47個と一致する
+fn get_weekday_pieces() -> Vec<Vec<Vec<u8>>> {
+    vec![
+        vec![vec![1, 0], vec![1, 0], vec![1, 0], vec![1, 1]],
+        vec![vec![0, 1], vec![0, 1], vec![1, 1], vec![1, 0]],
+        vec![vec![1, 1], vec![1, 1], vec![1, 0]],
+        vec![vec![1, 0, 1], vec![1, 1, 1]],
+        vec![vec![1, 0, 0], vec![1, 0, 0], vec![1, 1, 1]],
+        vec![vec![1, 1, 0], vec![0, 1, 0], vec![0, 1, 1]],
+        vec![vec![0, 1], vec![1, 1], vec![0, 1], vec![0, 1]],
+        vec![vec![1, 1, 1, 1]],
+        vec![vec![1, 0], vec![1, 0], vec![1, 1]],
+        vec![vec![1, 1, 0], vec![0, 1, 1]],
+    ]
+}
+
+/// 幅7列・高さ`rows`行の盤面で、最初の空きマスを覆う配置だけを試して全解を探索する再帰関数
+/// `find_solutions_recursive`の、ピースの個数とサイズを問わない版で、島の枝刈りには`judge_islands_for_sizes`を使う
+/// `used_placements`の要素iはピースiの配置ビットマスク（未配置なら0）、`cell_placements`は`cell_placement_index`の索引
+fn find_solutions_indexed_in_rows(
+    current_board: Board, used_placements: &mut [u64], cell_placements: &[Vec<(usize, u64)>],
+    piece_sizes: &[usize], rows: usize, solutions: &mut Vec<Vec<u64>>,
+) {
+    let empty = !current_board.0 & ((1u64 << (rows * 7)) - 1);
+    if empty == 0 {
+        if used_placements.iter().all(|&mask| mask != 0) { solutions.push(used_placements.to_vec()); }
+        return;
+    }
+
+    for &(piece_idx, placement_mask) in &cell_placements[empty.trailing_zeros() as usize] {
+        if used_placements[piece_idx] == 0 && !current_board.overlaps(&Board(placement_mask)) {
+            let new_board = current_board.union(&Board(placement_mask));
+            used_placements[piece_idx] = placement_mask;
+            let remaining_sizes: Vec<usize> = piece_sizes.iter().zip(used_placements.iter())
+                .filter(|&(_, &mask)| mask == 0)
+                .map(|(&size, _)| size)
+                .collect();
+            if judge_islands_for_sizes(new_board, &remaining_sizes, rows) {
+                find_solutions_indexed_in_rows(new_board, used_placements, cell_placements, piece_sizes, rows, solutions);
+            }
+            used_placements[piece_idx] = 0;
+        }
+    }
+}
+
+/// 曜日つきの盤面で、指定された月日と曜日の全解を求める
+/// 返す盤面は8x7で、日付と曜日の3つの穴は-1、固定の穴は0で表す
+fn solutions_for_date_weekday(month: u32, day: u32, weekday: u32) -> Vec<Solution> {
+    let mut start_board = Board::default();
+    for &(r, c) in &WEEKDAY_FIXED_HOLES { start_board.set_bit(r * 7 + c); }
+    let date_cells = [month_cell(month), day_cell(day), weekday_cell(weekday)];
+    for &(r, c) in &date_cells { start_board.set_bit(r * 7 + c); }
+
+    let shapes = get_weekday_pieces();
+    let all_piece_placements: Vec<Vec<u64>> = shapes.iter().map(|shape| shape_placements_in_rows(shape, WEEKDAY_ROWS)).collect();
+    let piece_sizes: Vec<usize> = shapes.iter().map(|shape| shape.iter().flatten().filter(|&&cell| cell == 1).count()).collect();
+
+    let mut found_raw_solutions = Vec::new();
+    find_solutions_indexed_in_rows(
+        start_board, &mut vec![0; shapes.len()], &cell_placement_index(&all_piece_placements),
+        &piece_sizes, WEEKDAY_ROWS, &mut found_raw_solutions,
+    );
+    found_raw_solutions.iter().map(|masks| {
+        let mut board = vec![vec![0i8; 7]; WEEKDAY_ROWS];
+        for (piece_id, &mask) in masks.iter().enumerate() {
+            for i in 0..WEEKDAY_ROWS * 7 {
+                if (mask >> i) & 1 == 1 { board[i / 7][i % 7] = (piece_id + 1) as i8; }
+            }
+        }
+        for &(r, c) in &date_cells { board[r][c] = -1; }
+        Solution { board, orientations_used: count_orientations_used(masks, &shapes) }
+    }).collect()
+}
+
+/// 月・日・曜日の3つの穴を残す曜日つきの盤面を解き、全解を返す - `weekday`は0（日曜）〜6（土曜）
+/// 標準の盤面とは穴の配置もピース集合も異なるので、`solve_for_date`とは別の探索を行う
+/// ピースが10個あって解の数も多いため、全解の列挙には標準の盤面よりずっと時間がかかる
+#[wasm_bindgen]
+pub fn solve_for_date_weekday(month: u32, day: u32, weekday: u32) -> Result<JsValue, JsValue> {
+    console_error_panic_hook::set_once();
+    validate_date(month, day)?;
+    if weekday > 6 { return Err(JsValue::from_str(&format!("曜日は0（日曜）〜6（土曜）で指定してください: {}", weekday))); }
+
+    let solutions = solutions_for_date_weekday(month, day, weekday);
+    Ok(serde_wasm_bindgen::to_value(&solutions)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 標準の盤面とピース集合での、各月の1日から順の解の個数（合計24405個）
+    const EXPECTED_COUNTS: [&[u32]; 12] = [
+        &[64, 109, 47, 103, 83, 24, 188, 79, 127, 77, 74, 60, 129, 88, 87, 70, 133, 69, 104, 195, 104, 78, 188, 48, 216, 84, 85, 145, 74, 119, 170],
+        &[73, 45, 22, 41, 49, 27, 88, 48, 32, 47, 26, 31, 59, 31, 28, 26, 55, 35, 45, 67, 29, 25, 81, 21, 78, 63, 47, 81, 64],
+        &[17, 24, 39, 57, 32, 16, 86, 66, 71, 27, 40, 40, 53, 44, 39, 30, 60, 22, 52, 61, 29, 29, 92, 24, 61, 72, 49, 80, 49, 18, 107],
+        &[55, 49, 78, 56, 63, 8, 104, 111, 52, 88, 68, 47, 63, 62, 59, 61, 62, 41, 52, 123, 76, 49, 145, 26, 116, 81, 68, 140, 84, 52],
+        &[57, 62, 32, 47, 56, 23, 116, 42, 33, 48, 51, 23, 77, 53, 56, 43, 67, 36, 46, 62, 36, 16, 87, 14, 101, 53, 47, 130, 66, 79, 87],
+        &[56, 49, 54, 48, 50, 24, 191, 85, 52, 44, 78, 45, 44, 61, 57, 38, 75, 42, 86, 114, 57, 35, 102, 31, 150, 73, 73, 163, 57, 39],
+        &[99, 19, 27, 70, 34, 12, 125, 63, 72, 40, 43, 37, 70, 48, 28, 40, 109, 43, 61, 108, 52, 24, 78, 22, 97, 38, 68, 133, 42, 51, 82],
+        &[68, 85, 40, 94, 76, 44, 172, 105, 88, 87, 57, 59, 120, 70, 72, 85, 107, 65, 100, 116, 53, 41, 129, 24, 161, 84, 111, 189, 82, 78, 151],
+        &[34, 18, 49, 38, 25, 27, 92, 50, 43, 51, 36, 20, 46, 21, 58, 42, 52, 34, 33, 70, 38, 29, 85, 24, 54, 48, 39, 78, 48, 36],
+        &[58, 34, 28, 59, 13, 7, 92, 50, 36, 59, 28, 16, 52, 54, 43, 26, 48, 26, 50, 67, 25, 27, 87, 22, 85, 36, 29, 95, 56, 29, 106],
+        &[82, 91, 75, 68, 178, 33, 179, 108, 72, 97, 102, 92, 78, 76, 67, 64, 115, 81, 82, 164, 104, 40, 101, 35, 155, 106, 86, 183, 85, 74],
+        &[26, 32, 67, 45, 32, 66, 125, 67, 73, 66, 44, 78, 99, 39, 81, 68, 82, 38, 59, 103, 44, 49, 115, 30, 92, 48, 71, 164, 54, 60, 77],
+    ];
+
+    /// 全日付の解の個数 - 全日付を探索するのは時間がかかるので、テスト全体で1回だけ数えて共有する
+    static DATE_COUNTS: std::sync::LazyLock<Vec<DateCount>> = std::sync::LazyLock::new(count_solutions_all_dates);
+
+    /// 全日付の全解 - `all_dates`の順に並べ、`DATE_COUNTS`と同じくテスト全体で共有する
+    static CALENDAR_SOLUTIONS: std::sync::LazyLock<Vec<Vec<[u64; 8]>>> = std::sync::LazyLock::new(|| {
+        all_dates().map(|(month, day)| find_raw_solutions(month, day, compute_piece_placements())).collect()
+    });
+
+    fn expected_count(month: u32, day: u32) -> u32 {
+        EXPECTED_COUNTS[month as usize - 1][day as usize - 1]
+    }
+
+    #[test]
+    fn replay_steps_are_overlap_free_and_end_at_the_solution() {
+        for (month, day) in [(1, 1), (3, 15), (12, 31)] {
+            for solution in solutions_for_date(month, day) {
+                let steps = replay_solution(&solution);
+                assert_eq!(steps.len(), 8);
+                assert!(steps.iter().all(|step| step.no_overlap));
+                assert_eq!(steps.last().unwrap().board, solution.board);
+
+                // 探索と同じく、各ステップのピースはその時点で最も番号の小さい空きマスを埋めている
+                let mut occupied = initial_board(month, day);
+                for step in &steps {
+                    let first_empty = occupied.0.trailing_ones() as usize;
+                    assert!(step.cells.contains(&((first_empty / 7) as u8, (first_empty % 7) as u8)));
+                    for &(r, c) in &step.cells { occupied.set(r as usize, c as usize); }
+                }
+            }
+        }
+
+        // ピース8のマスをピース7で塗りつぶすと、ピース8を置くステップが検証に失敗する
+        let mut broken = solutions_for_date(1, 1).remove(0);
+        for cell in broken.board.iter_mut().flatten() {
+            if *cell == 8 { *cell = 7; }
+        }
+        assert!(!replay_solution(&broken)[7].no_overlap);
+    }
+
+    #[test]
+    fn easiest_date_has_the_calendar_maximum() {
+        let (_, easiest) = solution_count_extremes(&DATE_COUNTS).unwrap();
+        let max = EXPECTED_COUNTS.iter().copied().flatten().copied().max().unwrap();
+        assert_eq!(easiest.count, max);
+        assert_eq!((easiest.month, easiest.day), (1, 25));
+        assert_eq!(expected_count(easiest.month, easiest.day), easiest.count);
+    }
+
+    #[test]
+    fn self_symmetric_solutions_need_a_symmetric_board() {
+        // DragonFjordの盤面は固定の穴が非対称なので、線対称な解は存在しない
+        for (month, day) in [(1, 1), (7, 4), (11, 25)] {
+            assert!(solutions_for_date(month, day).iter().all(|solution| !is_self_symmetric(&solution.board)));
+        }
+
+        // 左右対称に塗り分けた盤面は線対称と判定され、1マスでも崩すと判定されない
+        let mut board: Vec<Vec<i8>> = (0..7).map(|r| (0..7).map(|c| (r % 2 * 4 + c.min(6 - c) + 1) as i8).collect()).collect();
+        assert!(is_self_symmetric(&board));
+        board[0][0] = 8;
+        assert!(!is_self_symmetric(&board));
+    }
+
+    #[test]
+    fn canonical_board_distance_to_itself_is_zero() {
+        for (month, day) in [(1, 1), (3, 15), (12, 31)] {
+            let solution = canonical_solution_for_date(month, day).unwrap();
+            assert_eq!(board_distance(&solution.board, &solution.board), 0);
+        }
+        let first = canonical_solution_for_date(1, 1).unwrap();
+        let second = canonical_solution_for_date(1, 2).unwrap();
+        assert!(board_distance(&first.board, &second.board) > 0);
+    }
+
+    #[test]
+    fn optional_pieces_cover_a_board_left_by_a_strict_subset() {
+        // 1月1日の解からピース7と8のマスも埋めておくと、残りはピース1〜6だけでちょうど埋まる
+        let solution = solutions_for_date(1, 1).remove(0);
+        let blocked: Vec<Vec<u8>> = solution.board.iter().map(|row| row.iter().map(|&cell| u8::from(cell <= 0 || cell >= 7)).collect()).collect();
+        let start_board = Board::from_grid(&blocked);
+        let expected: Vec<Vec<i8>> = solution.board.iter().zip(&blocked)
+            .map(|(row, blocked_row)| row.iter().zip(blocked_row).map(|(&cell, &b)| if b == 1 { -1 } else { cell }).collect())
+            .collect();
+
+        let solutions = solutions_with_optional_pieces(start_board).unwrap();
+        assert!(solutions.iter().any(|s| s.board == expected));
+        for s in &solutions {
+            for (row, blocked_row) in s.board.iter().zip(&blocked) {
+                for (&cell, &b) in row.iter().zip(blocked_row) {
+                    assert_eq!(cell == -1, b == 1);
+                    assert!(cell != 0);
+                }
+            }
+        }
+
+        // 空きマスが全ピースの合計面積より多い盤面はエラーになる
+        assert!(solutions_with_optional_pieces(Board::default()).is_err());
+    }
+
+    #[test]
+    fn monthly_averages_fall_within_each_months_range() {
+        let averages = monthly_average_counts(&DATE_COUNTS);
+        assert_eq!(averages.len(), 12);
+        for (&(month, average), expected) in averages.iter().zip(EXPECTED_COUNTS) {
+            let min = *expected.iter().min().unwrap() as f64;
+            let max = *expected.iter().max().unwrap() as f64;
+            assert!((min..=max).contains(&average), "{}月の平均{}が{}〜{}の範囲外です", month, average, min, max);
+        }
+    }
+
+    #[test]
+    fn piece_copies_do_not_count_swapped_duplicates() {
+        // ピース5をピース4と同じ形に差し替えた集合を、2個のコピーとして渡す場合と別々のピースとして渡す場合で比べる
+        let pieces = get_initial_pieces();
+        let mut separate: Vec<(Vec<Vec<u8>>, u32)> = pieces.iter().map(|shape| (shape.clone(), 1)).collect();
+        separate[4] = (pieces[3].clone(), 1);
+        let mut copies = separate.clone();
+        copies.remove(4);
+        copies[3].1 = 2;
+
+        // 2個のコピーはIDが4と5の通し番号になるので、同じIDにそろえてから比べる
+        let merged_boards = |solutions: Vec<Solution>| -> Vec<Vec<Vec<i8>>> {
+            solutions.into_iter()
+                .map(|solution| solution.board.iter().map(|row| row.iter().map(|&cell| if cell == 5 { 4 } else { cell }).collect()).collect())
+                .collect()
+        };
+        let separate_boards = merged_boards(solutions_with_piece_counts(11, 25, &separate).unwrap());
+        let mut copy_boards = merged_boards(solutions_with_piece_counts(11, 25, &copies).unwrap());
+        assert!(!copy_boards.is_empty());
+        assert_eq!(separate_boards.len(), 2 * copy_boards.len());
+
+        let mut unique_separate_boards = separate_boards.clone();
+        unique_separate_boards.sort();
+        unique_separate_boards.dedup();
+        copy_boards.sort();
+        let copy_count = copy_boards.len();
+        copy_boards.dedup();
+        assert_eq!(copy_boards.len(), copy_count);
+        assert_eq!(copy_boards, unique_separate_boards);
+
+        // 個数が盤面に収まらないほど大きい集合や、埋まったマスの無い形状は、コピーを展開する前にエラーになる
+        copies[3].1 = u32::MAX;
+        assert!(build_custom_pieces(&copies).is_err());
+        assert!(solutions_with_piece_counts(11, 25, &copies).is_err());
+        assert!(build_custom_pieces(&[(vec![vec![0, 0]], 1)]).is_err());
+    }
+
+    #[test]
+    fn every_open_cell_is_fillable_on_the_standard_board() {
+        for (month, day) in all_dates() {
+            let open_cells = !initial_board(month, day).0 & FULL_BOARD_MASK;
+            assert_eq!(fillable_cells_mask(month, day).unwrap(), open_cells, "{}月{}日", month, day);
+        }
+    }
+
+    #[test]
+    fn branching_profile_has_one_level_per_piece() {
+        let cell_placements = cell_placement_index(compute_piece_placements());
+        for (month, day) in [(1, 1), (3, 1), (11, 25)] {
+            let profile = branching_profile_for_date(month, day);
+            assert_eq!(profile.len(), 8);
+            assert_eq!(profile[7], expected_count(month, day) as u64);
+
+            // 子ノードの総数に根ノードを加えると、探索が訪れるノードの数になる
+            let mut nodes = 0u64;
+            run_interruptible_search(initial_board(month, day), [0; 8], &cell_placements, &mut |_: &[u64; 8]| true, &mut || {
+                nodes += 1;
+                true
+            });
+            assert_eq!(profile.iter().sum::<u64>() + 1, nodes);
+        }
+    }
+
+    #[test]
+    fn first_n_solutions_are_a_prefix_of_the_full_list() {
+        for (month, day) in [(1, 1), (7, 4), (11, 25)] {
+            let all = find_raw_solutions(month, day, compute_piece_placements());
+            assert_eq!(first_n_raw_solutions(month, day, 5, compute_piece_placements()), all[..5]);
+            assert!(first_n_raw_solutions(month, day, 0, compute_piece_placements()).is_empty());
+            assert_eq!(first_n_raw_solutions(month, day, all.len() + 10, compute_piece_placements()), all);
+        }
+    }
+
+    #[test]
+    fn board_operations_match_the_raw_bit_arithmetic() {
+        let masks = [0, 1, FULL_BOARD_MASK, 0x0F0F_0F0F_0F0F & FULL_BOARD_MASK, initial_board(3, 15).0];
+        for &a in &masks {
+            let board = Board(a);
+            for r in 0..7 {
+                for c in 0..7 {
+                    assert_eq!(board.get(r, c), (a >> (r * 7 + c)) & 1 == 1);
+                    let mut set = board;
+                    assert!(set.set(r, c));
+                    assert_eq!(set.0, a | 1 << (r * 7 + c));
+                }
+            }
+
+            // 盤面の外のマスは、シフトがはみ出す行でも埋まらず、埋まっているとも判定されない
+            for (r, c) in [(7, 0), (0, 7), (10, 0), (0, 64), (usize::MAX, usize::MAX)] {
+                let mut set = board;
+                assert!(!set.set(r, c));
+                assert_eq!(set, board);
+                assert!(!board.get(r, c));
+            }
+            assert_eq!(board.is_full(), a == FULL_BOARD_MASK);
+            assert_eq!(board.empty_cells(), 49 - a.count_ones());
+            assert_eq!(Board::from_grid(&board.to_grid()), board);
+            for &b in &masks {
+                assert_eq!(board.overlaps(&Board(b)), a & b != 0);
+                assert_eq!(board.union(&Board(b)).0, a | b);
+            }
+        }
+    }
+
+    #[test]
+    fn symmetric_dates_need_symmetric_fixed_holes() {
+        assert!(symmetric_dates_with(&FIXED_HOLES).is_empty());
+
+        // 固定の穴がなければ、3月のマス (0, 2) と1日のマス (2, 0) は主対角線で折り返すと移り合う
+        let dates = symmetric_dates_with(&[]);
+        assert!(dates.contains(&(3, 1)));
+        assert!(!dates.contains(&(1, 1)));
+        assert_eq!(board_automorphisms(initial_board(3, 1)), []);
+    }
+
+    #[test]
+    fn iterative_and_recursive_search_find_the_same_solutions() {
+        // フィーチャーの有無によらず、2つの探索を直接呼び出して比べる
+        let cell_placements = cell_placement_index(compute_piece_placements());
+        let piece_sizes = indexed_piece_sizes(&cell_placements);
+        for (month, day) in [(1, 1), (3, 1), (3, 15), (5, 14), (11, 25), (12, 31)] {
+            let mut recursive = Vec::new();
+            find_solutions_recursive(initial_board(month, day), &mut [0; 8], &cell_placements, &piece_sizes, &mut |masks: &[u64; 8]| {
+                recursive.push(*masks);
+                true
+            }, &mut || true);
+            let mut iterative = Vec::new();
+            find_solutions_iterative(initial_board(month, day), &mut [0; 8], &cell_placements, &piece_sizes, &mut |masks: &[u64; 8]| {
+                iterative.push(*masks);
+                true
+            }, &mut || true);
+            assert_eq!(recursive.len() as u32, expected_count(month, day));
+            assert_eq!(iterative, recursive, "{}月{}日", month, day);
+        }
+    }
+
+    #[test]
+    fn piece_flexibility_is_positive_for_used_pieces_and_zero_for_excluded_ones() {
+        let raw_solutions = find_raw_solutions(7, 4, compute_piece_placements());
+        assert!(placement_frequencies(&raw_solutions).iter().all(|frequencies| !frequencies.is_empty()));
+
+        // ピース8を除いた置き方では、ピース8の配置は1つも現れない
+        let subset_solutions: Vec<[u64; 8]> = solutions_for_subset(3, 1, &[8]).unwrap().iter()
+            .map(|solution| solution_piece_masks(solution).try_into().unwrap())
+            .collect();
+        assert!(!subset_solutions.is_empty());
+        let flexibility: Vec<usize> = placement_frequencies(&subset_solutions).iter().map(|frequencies| frequencies.len()).collect();
+        assert_eq!(flexibility[7], 0);
+        assert!(flexibility[..7].iter().all(|&n| n > 0));
+    }
+
+    #[test]
+    fn empty_board_svg_labels_every_month_and_day() {
+        let svg = empty_board_svg("dragon_fjord", 40).unwrap();
+        for label in MONTH_LABELS.iter().map(|label| label.to_string()).chain((1..=31).map(|day| day.to_string())) {
+            assert_eq!(svg.matches(&format!(">{}</text>", label)).count(), 1, "{}", label);
+        }
+        assert_eq!(svg.matches("<text").count(), 12 + 31);
+        assert_eq!(svg.matches(r##"fill="#444""##).count(), FIXED_HOLES.len());
+    }
+
+    #[test]
+    fn inverted_puzzle_covers_exactly_the_former_holes() {
+        let config = SolveConfig { invert_holes: true, sort_by: SortKey::Search };
+        assert!(solutions_for_config(1, 1, &config).is_err());
+
+        // 裏返した1月1日の盤面の空きマスは、固定の穴と日付の穴の8マス - 縦2マス・横4マス・1マス2個のピースでちょうど埋まる
+        let holes = initial_board(1, 1);
+        let scenario = Scenario {
+            version: SCENARIO_VERSION,
+            variant: "dragon_fjord".to_string(),
+            month: 1,
+            day: 1,
+            pieces: vec![vec![vec![1], vec![1]], vec![vec![1, 1, 1, 1]], vec![vec![1]], vec![vec![1]]],
+            holes: holes.to_grid(),
+            config,
+        };
+        let result = run_scenario_internal(&scenario).unwrap();
+        assert!(result.count > 0);
+        for solution in &result.solutions {
+            for (r, row) in solution.board.iter().enumerate() {
+                for (c, &cell) in row.iter().enumerate() {
+                    assert_eq!(cell > 0, holes.get(r, c), "({}, {})", r, c);
+                    assert_eq!(cell == -1, !holes.get(r, c), "({}, {})", r, c);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn solution_count_trajectory_is_non_increasing_and_ends_at_one() {
+        for (month, day) in [(1, 1), (11, 25)] {
+            for solution in solutions_for_date(month, day).iter().take(3) {
+                let trajectory = count_trajectory(solution);
+                assert_eq!(trajectory.len(), 8);
+                assert!(trajectory[0] <= expected_count(month, day));
+                assert!(trajectory.windows(2).all(|pair| pair[0] >= pair[1]));
+                assert_eq!(trajectory[7], 1);
+            }
+        }
+    }
+
+    #[test]
+    fn bulk_solving_matches_individual_calls() {
+        let dates = [(1, 1), (3, 1), (12, 31), (1, 1)];
+        let results = solutions_for_dates(&dates);
+        assert_eq!(results.len(), dates.len());
+        for (result, &(month, day)) in results.iter().zip(&dates) {
+            assert_eq!((result.month, result.day), (month, day));
+            assert_eq!(result.solutions, solutions_for_date(month, day));
+        }
+    }
+
+    #[test]
+    fn scenario_round_trip_reproduces_the_solution_count() {
+        for (month, day) in [(1, 1), (3, 1), (7, 4)] {
+            for sort_by in [SortKey::Search, SortKey::Balance] {
+                let result = run_scenario_internal(&scenario_for_date(month, day, SolveConfig { invert_holes: false, sort_by })).unwrap();
+                assert_eq!(result.count, expected_count(month, day));
+                assert_eq!(result.solutions.len() as u32, result.count);
+            }
+        }
+
+        let mut scenario = scenario_for_date(1, 1, SolveConfig::default());
+        scenario.version += 1;
+        assert!(run_scenario_internal(&scenario).is_err());
+    }
+
+    #[test]
+    fn median_difficulty_date_is_closest_to_the_median() {
+        let (median, closest) = median_difficulty(&DATE_COUNTS).unwrap();
+        let (hardest, easiest) = solution_count_extremes(&DATE_COUNTS).unwrap();
+        assert!((hardest.count..=easiest.count).contains(&closest.count));
+        assert_eq!(expected_count(closest.month, closest.day), closest.count);
+        let distance = |count: u32| (count as f64 - median).abs();
+        assert!(DATE_COUNTS.iter().all(|c| distance(closest.count) <= distance(c.count)));
+        // 同じ距離の日付が複数あれば、カレンダー上で最も早い日付を選ぶ
+        let first_closest = DATE_COUNTS.iter().find(|c| distance(c.count) == distance(closest.count)).unwrap();
+        assert_eq!((first_closest.month, first_closest.day), (closest.month, closest.day));
+    }
+
+    #[test]
+    fn orientation_usage_counts_non_base_orientations() {
+        // 全ピースを基本形の向きで置いた配置は0になる（カレンダーの解には無く、最小は2月19日の解の2個）
+        let base_masks: Vec<u64> = get_initial_pieces().iter().map(|shape| normalize_mask(Board::from_grid(shape).0)).collect();
+        assert_eq!(standard_orientations_used(&base_masks), 0);
+        assert_eq!(count_orientations_used(&base_masks, &get_initial_pieces()), 0);
+
+        for (month, day) in [(1, 1), (2, 19), (11, 25)] {
+            for masks in find_raw_solutions(month, day, compute_piece_placements()) {
+                let orientations_used = standard_orientations_used(&masks);
+                assert_eq!(orientations_used, count_orientations_used(&masks, &get_initial_pieces()));
+                assert_eq!(masks_to_solution(&masks, month, day).orientations_used, orientations_used);
+                assert!(orientations_used >= 2);
+            }
+        }
+    }
+
+    #[test]
+    fn rarest_placements_have_positive_ascending_counts() {
+        let all = rarest_placements_in(CALENDAR_SOLUTIONS.iter().cloned(), usize::MAX);
+        assert!(all.iter().all(|&(_, _, count)| count > 0));
+        assert!(all.windows(2).all(|pair| (pair[0].2, pair[0].0, pair[0].1) <= (pair[1].2, pair[1].0, pair[1].1)));
+        // 全解のピースごとの配置を数えているので、出現回数の合計は解の総数の8倍になる
+        assert_eq!(all.iter().map(|&(_, _, count)| count).sum::<u32>(), 8 * 24405);
+
+        let rarest = rarest_placements_in(CALENDAR_SOLUTIONS.iter().cloned(), 5);
+        assert_eq!(rarest, all[..5]);
+    }
+
+    #[test]
+    fn rle_decoding_reproduces_the_board_and_is_shorter() {
+        for (month, day) in [(1, 1), (7, 4), (11, 25)] {
+            for solution in solutions_for_date(month, day) {
+                let encoded = encode_board_rle(&solution.board);
+                assert_eq!(decode_board_rle(&encoded).unwrap(), solution.board);
+                assert!(encoded.len() < 49, "{}", encoded);
+            }
+        }
+
+        assert!(decode_board_rle("").is_err());
+        assert!(decode_board_rle("48A").is_err());
+        assert!(decode_board_rle("50A").is_err());
+        assert!(decode_board_rle("49Z").is_err());
+        assert_eq!(decode_board_rle("49A").unwrap(), vec![vec![1; 7]; 7]);
+        assert_eq!(decode_board_rle("47AB.").unwrap(), decode_board_rle("47A1B1.").unwrap());
+        // 桁数の多い個数もあふれずにエラーになる
+        assert_eq!(decode_board_rle(&"9".repeat(40)).unwrap_err(), "マスの数が49を超えています");
+    }
+
+    #[test]
+    fn every_solution_has_pieces_touching_the_date() {
+        for ((month, day), raw_solutions) in all_dates().zip(CALENDAR_SOLUTIONS.iter()) {
+            for masks in raw_solutions {
+                let piece_ids = pieces_adjacent_to_date(&masks_to_solution(masks, month, day).board, month, day);
+                assert!(!piece_ids.is_empty(), "{}月{}日", month, day);
+                assert!(piece_ids.windows(2).all(|pair| pair[0] < pair[1]));
+            }
+        }
+
+        let piece_sets = date_neighbor_piece_sets(11, 25);
+        assert!(!piece_sets.is_empty());
+        assert!(piece_sets.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn balance_sort_orders_a_hand_made_example() {
+        let board_with = |cells: &[(usize, usize, i8)]| {
+            let mut board = vec![vec![0i8; 7]; 7];
+            for &(r, c, piece_id) in cells { board[r][c] = piece_id; }
+            Solution { board, orientations_used: 0 }
+        };
+        // ピース1・2とも重心が中心にあるので分散は0、片方を隅に寄せると分散が正になる
+        let balanced = board_with(&[(3, 2, 1), (3, 4, 1), (2, 3, 2), (4, 3, 2)]);
+        let lopsided = board_with(&[(0, 0, 1), (0, 1, 1), (2, 3, 2), (4, 3, 2)]);
+        assert_eq!(balance_score(&balanced.board), 0.0);
+        assert!(balance_score(&lopsided.board) > 0.0);
+        assert_eq!(balance_score(&lopsided.board), balance_score(&lopsided.board));
+
+        let mut solutions = vec![lopsided.clone(), balanced.clone(), lopsided.clone()];
+        sort_solutions(&mut solutions, SortKey::Search);
+        assert_eq!(solutions, [lopsided.clone(), balanced.clone(), lopsided.clone()]);
+        sort_solutions(&mut solutions, SortKey::Balance);
+        assert_eq!(solutions, [balanced, lopsided.clone(), lopsided]);
+    }
+
+    #[cfg(feature = "dlx")]
+    #[test]
+    fn dlx_cross_check_agrees_on_every_date() {
+        for (month, day) in all_dates() {
+            assert_eq!(cross_check_count_internal(month, day), Ok(expected_count(month, day)), "{}月{}日", month, day);
+        }
+    }
+
+    #[test]
+    fn center_placements_cover_the_center_and_avoid_holes() {
+        let center_mask = 1u64 << (3 * 7 + 3);
+        for (month, day) in [(1, 1), (3, 15), (12, 31)] {
+            let placements = center_placements_for_date(month, day);
+            assert!(!placements.is_empty());
+            let holes = initial_board(month, day);
+            for &(piece_idx, placement_mask) in &placements {
+                assert!(placement_mask & center_mask != 0);
+                assert!(!holes.overlaps(&Board(placement_mask)));
+                assert!(compute_piece_placements()[piece_idx].contains(&placement_mask));
+            }
+        }
+        // 11日は中心が日付の穴になるので、中心を覆える配置はない
+        assert!(center_placements_for_date(5, 11).is_empty());
+    }
+
+    #[test]
+    fn draining_the_channel_yields_every_solution_in_order() {
+        for (month, day) in [(1, 1), (3, 1), (11, 25)] {
+            let (tx, rx) = std::sync::mpsc::channel();
+            let sender = std::thread::spawn(move || solve_to_channel(month, day, tx));
+            let received: Vec<Solution> = rx.iter().collect();
+            assert_eq!(sender.join().unwrap(), Ok(()));
+            assert_eq!(received, solutions_for_date(month, day));
+        }
+
+        // 受信側を先に破棄しても、探索は送信に失敗した時点で終わる
+        let (tx, rx) = std::sync::mpsc::channel();
+        drop(rx);
+        assert_eq!(solve_to_channel(11, 25, tx), Ok(()));
+    }
+
+    #[test]
+    fn signature_piece_touches_the_date_in_some_solution() {
+        for (month, day) in [(1, 1), (5, 14), (11, 25)] {
+            let piece_id = signature_piece(month, day).unwrap();
+            assert!((1..=8).contains(&piece_id));
+            assert!(date_neighbor_piece_sets(month, day).iter().any(|piece_ids| piece_ids.contains(&piece_id)));
+        }
+    }
+
+    #[test]
+    fn grouping_by_a_cell_partitions_the_solutions() {
+        for (month, day, cell) in [(2, 1, [0, 0]), (11, 25, [3, 3]), (7, 4, [6, 0])] {
+            let groups = group_solutions_by_piece_at(month, day, cell).unwrap();
+            assert_eq!(groups.iter().map(|(_, group)| group.len() as u32).sum::<u32>(), expected_count(month, day));
+            assert!(groups.windows(2).all(|pair| pair[0].0 < pair[1].0));
+            for (piece_id, group) in &groups {
+                assert!(group.iter().all(|solution| solution.board[cell[0]][cell[1]] == *piece_id));
+            }
+        }
+        assert!(group_solutions_by_piece_at(1, 1, [0, 0]).is_err());
+        assert!(group_solutions_by_piece_at(1, 1, [0, 6]).is_err());
+        assert!(group_solutions_by_piece_at(1, 1, [7, 0]).is_err());
+    }
+
+    #[test]
+    fn invalid_dates_are_rejected_at_every_boundary() {
+        // `JsValue`はネイティブ環境では作れないので、wasm向けの関数が使う`is_valid_date`とネイティブ向けの入口で確かめる
+        let mut invalid = vec![(0, 1), (13, 1), (0, 0), (13, 31)];
+        let mut valid = Vec::new();
+        for month in 1..=12 {
+            invalid.push((month, 0));
+            for day in 29..=32 {
+                if day <= days_in_month(month) { valid.push((month, day)) } else { invalid.push((month, day)) }
+            }
+        }
+        assert!(invalid.contains(&(2, 30)) && invalid.contains(&(4, 31)));
+        assert!(valid.contains(&(2, 29)) && valid.contains(&(12, 31)));
+
+        for &(month, day) in &invalid {
+            assert!(!is_valid_date(month, day), "{}月{}日", month, day);
+            assert_eq!(solve_raw(month, day), Err(invalid_date_message(month, day)));
+            assert_eq!(solve_streaming(month, day, |_| {}), Err(invalid_date_message(month, day)));
+            let (tx, rx) = std::sync::mpsc::channel();
+            assert_eq!(solve_to_channel(month, day, tx), Err(invalid_date_message(month, day)));
+            assert!(rx.try_recv().is_err());
+        }
+        for &(month, day) in &valid {
+            assert!(is_valid_date(month, day), "{}月{}日", month, day);
+            assert_eq!(fillable_cells_mask(month, day).unwrap(), !initial_board(month, day).0 & FULL_BOARD_MASK);
+            assert!((0..=8).contains(&signature_piece(month, day).unwrap()));
+        }
+        assert_eq!(invalid_date_message(13, 1), r#"{"error":"invalid_date","month":13,"day":1}"#);
+    }
+
+    #[test]
+    fn count_only_search_matches_the_full_solution_list() {
+        for (month, day) in [(1, 1), (3, 1), (5, 14), (7, 4), (11, 25), (12, 31)] {
+            let count = count_raw_solutions(month, day, compute_piece_placements());
+            assert_eq!(count as usize, solutions_for_date(month, day).len());
+            assert_eq!(count, expected_count(month, day));
+        }
+    }
+
+    #[test]
+    fn piece_placements_are_computed_once_and_match_a_fresh_computation() {
+        assert!(std::ptr::eq(compute_piece_placements(), compute_piece_placements()));
+        let fresh: Vec<Vec<u64>> = get_initial_pieces().iter().map(|shape| shape_placements(shape)).collect();
+        assert_eq!(compute_piece_placements(), fresh);
+    }
+
+    #[test]
+    fn limited_solutions_are_a_prefix_of_the_full_result() {
+        for (month, day) in [(1, 1), (3, 1), (11, 25)] {
+            let all = solutions_for_date(month, day);
+            assert_eq!(limited_solutions(month, day, 0), all);
+            for max_solutions in [1, 5, 200] {
+                let limited = limited_solutions(month, day, max_solutions);
+                assert_eq!(limited.len(), all.len().min(max_solutions as usize));
+                assert_eq!(limited, all[..limited.len()]);
+            }
+        }
+    }
+
+    #[test]
+    fn first_solution_is_none_only_for_unsolvable_dates() {
+        // 標準のピース集合では全ての日付に解があり、探索順で最初の解が返る
+        for ((month, day), raw_solutions) in all_dates().zip(CALENDAR_SOLUTIONS.iter()) {
+            let expected = raw_solutions.first().map(|masks| masks_to_solution(masks, month, day));
+            assert!(expected.is_some());
+            assert_eq!(first_solution(month, day, compute_piece_placements()), expected, "{}月{}日", month, day);
+        }
+
+        // ピースを裏返せない盤面では1月14日に解が無く、解の代わりに`None`（JavaScript側では`null`）になる
+        assert_eq!(count_raw_solutions(1, 14, &SINGLE_SIDED_PIECE_PLACEMENTS), 0);
+        assert!(first_solution(1, 14, &SINGLE_SIDED_PIECE_PLACEMENTS).is_none());
+    }
+
+    #[test]
+    fn weekday_boards_leave_exactly_the_three_date_holes() {
+        let solutions = solutions_for_date_weekday(1, 1, 0);
+        assert_eq!(solutions.len(), 4020);
+        let date_cells = [month_cell(1), day_cell(1), weekday_cell(0)];
+        let piece_count = get_weekday_pieces().len() as i8;
+        for solution in &solutions {
+            assert_eq!(solution.board.len(), WEEKDAY_ROWS);
+            for (r, row) in solution.board.iter().enumerate() {
+                assert_eq!(row.len(), 7);
+                for (c, &cell) in row.iter().enumerate() {
+                    match cell {
+                        -1 => assert!(date_cells.contains(&(r, c))),
+                        0 => assert!(WEEKDAY_FIXED_HOLES.contains(&(r, c))),
+                        piece_id => assert!((1..=piece_count).contains(&piece_id)),
+                    }
+                }
+            }
+            assert_eq!(solution.board.iter().flatten().filter(|&&cell| cell == -1).count(), 3);
+        }
+    }
+
+    #[test]
+    fn default_layout_matches_the_original_hole_formula() {
+        for (month, day) in all_dates() {
+            let mut board = Board::default();
+            for &(r, c) in &FIXED_HOLES { board.set(r, c); }
+            board.set(((month - 1) / 6) as usize, ((month - 1) % 6) as usize);
+            board.set(((day - 1) / 7 + 2) as usize, ((day - 1) % 7) as usize);
+            assert_eq!(BoardLayout::default().initial_board(month, day), board);
+        }
+
+        let solutions = solutions_for_date(3, 15);
+        assert_eq!(solutions.len(), 39);
+        assert_eq!(solutions, solutions_for_layout(&BoardLayout::dragon_fjord(), 3, 15));
+    }
+
+    #[test]
+    fn detailed_pieces_cover_every_open_cell_exactly_once() {
+        for ((month, day), raw_solutions) in all_dates().zip(CALENDAR_SOLUTIONS.iter()) {
+            for masks in raw_solutions {
+                let detailed = masks_to_detailed(masks, month, day);
+                assert_eq!(detailed.pieces.len(), 8);
+                assert_eq!(detailed.holes, vec![month_cell(month), day_cell(day)].into_iter().map(|(r, c)| (r as u8, c as u8)).collect::<Vec<_>>());
+
+                let mut covered = HashSet::new();
+                for (piece_idx, piece) in detailed.pieces.iter().enumerate() {
+                    assert_eq!(piece.id as usize, piece_idx + 1);
+                    for &cell in &piece.cells {
+                        assert!(covered.insert(cell), "{}/{}: マス{:?}が重なっている", month, day, cell);
+                    }
+                }
+                // 7x7の49マスから固定の穴6マスと日付の穴2マスを除いた41マス
+                assert_eq!(covered.len(), 41);
+                assert!(detailed.holes.iter().all(|hole| !covered.contains(hole)));
+                assert!(FIXED_HOLES.iter().all(|&(r, c)| !covered.contains(&(r as u8, c as u8))));
+            }
+        }
+    }
+
+    #[test]
+    fn verifier_accepts_real_solutions_and_rejects_corrupted_boards() {
+        for (month, day) in [(1, 1), (3, 15), (11, 25), (12, 31)] {
+            for solution in solutions_for_date(month, day) {
+                assert!(is_valid_solution_board(month, day, &solution.board));
+            }
+        }
+
+        let solution = solutions_for_date(1, 1).remove(0);
+        // 別の日付の解としては、日付の穴の位置が合わない
+        assert!(!is_valid_solution_board(1, 2, &solution.board));
+
+        // 隣り合う別々のピースのマスを入れ替えると、どちらかのピースの形が崩れる
+        let mut swapped = solution.board.clone();
+        let (r, c) = (0..7).flat_map(|r| (0..6).map(move |c| (r, c)))
+            .find(|&(r, c)| swapped[r][c] > 0 && swapped[r][c + 1] > 0 && swapped[r][c] != swapped[r][c + 1]).unwrap();
+        swapped[r].swap(c, c + 1);
+        assert!(!is_valid_solution_board(1, 1, &swapped));
+
+        // 固定の穴を埋めたり、日付の穴を開け直したりした盤面も正しくない
+        let mut filled_fixed_hole = solution.board.clone();
+        filled_fixed_hole[0][6] = 1;
+        assert!(!is_valid_solution_board(1, 1, &filled_fixed_hole));
+        let mut extra_hole = solution.board.clone();
+        extra_hole[3][3] = -1;
+        assert!(!is_valid_solution_board(1, 1, &extra_hole));
+
+        // 7x7でない盤面も正しくない
+        assert!(!is_valid_solution_board(1, 1, &solution.board[..6]));
+
+        // 形が一致する配置は、盤面の右端・下端に接するものも含めて全て探索の配置パターンにあるので、検証と探索の判定は食い違わない
+        for (shape, placements) in get_initial_pieces().iter().zip(compute_piece_placements()) {
+            assert!(placements.iter().all(|&mask| orientation_index(shape, mask).is_some()));
+            for rotated in get_unique_rotations(shape) {
+                let normalized = normalize_mask(Board::from_grid(&rotated).0);
+                for r in 0..=7 - rotated.len() {
+                    for c in 0..=7 - rotated[0].len() {
+                        assert!(placements.binary_search(&(normalized << (r * 7 + c))).is_ok());
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn locking_pieces_of_a_known_solution_reproduces_it() {
+        let shapes = get_initial_pieces();
+        // 向きの形状は上下左右の端の行・列のどれにも埋まったマスがあるので、(row, col)はそのまま外接長方形の左上になる
+        for rotated in shapes.iter().flat_map(|shape| get_unique_rotations(shape)) {
+            assert!(rotated[0].contains(&1) && rotated[rotated.len() - 1].contains(&1));
+            assert!(rotated.iter().any(|row| row[0] == 1) && rotated.iter().any(|row| row[row.len() - 1] == 1));
+        }
+        let lock = |masks: &[u64; 8], piece_idx: usize| {
+            let mask = masks[piece_idx];
+            let cells: Vec<usize> = (0..49).filter(|&i| (mask >> i) & 1 == 1).collect();
+            LockedPiece {
+                piece_id: (piece_idx + 1) as u8,
+                row: cells.iter().map(|&i| i / 7).min().unwrap(),
+                col: cells.iter().map(|&i| i % 7).min().unwrap(),
+                rotation: orientation_index(&shapes[piece_idx], mask).unwrap(),
+            }
+        };
+
+        for (month, day) in [(1, 1), (3, 15), (11, 25)] {
+            let raw_solutions = find_raw_solutions(month, day, compute_piece_placements());
+            for masks in raw_solutions.iter().step_by(7) {
+                let expected = masks_to_solution(masks, month, day);
+                for locked_ids in [vec![2], vec![0, 5], (0..8).collect()] {
+                    let locked: Vec<LockedPiece> = locked_ids.iter().map(|&piece_idx| lock(masks, piece_idx)).collect();
+                    for piece in &locked {
+                        assert_eq!(locked_piece_mask(piece), Ok(masks[(piece.piece_id - 1) as usize]));
+                    }
+                    let solutions = solutions_with_locked(month, day, &locked).unwrap();
+                    assert!(solutions.contains(&expected));
+                    assert!(solutions.len() <= raw_solutions.len());
+                }
+            }
+        }
+
+        // 解のピースを別の日付で固定すると日付の穴と重なることがあり、同じピースの2回固定はエラーになる
+        let masks = find_raw_solutions(1, 1, compute_piece_placements())[0];
+        assert!(solutions_with_locked(1, 1, &[lock(&masks, 0), lock(&masks, 0)]).is_err());
+        let all_locked: Vec<LockedPiece> = (0..8).map(|piece_idx| lock(&masks, piece_idx)).collect();
+        assert!(solutions_with_locked(1, 2, &all_locked).is_err());
+    }
+
+    #[test]
+    fn every_entry_point_collects_the_same_solutions() {
+        for (month, day) in [(1, 1), (3, 1), (11, 25)] {
+            let raw_solutions = solve_raw(month, day).unwrap();
+            let solutions: Vec<Solution> = raw_solutions.iter().map(|masks| masks_to_solution(masks, month, day)).collect();
+            assert_eq!(raw_solutions, find_raw_solutions(month, day, compute_piece_placements()));
+            assert_eq!(solutions, solutions_for_date(month, day));
+            assert_eq!(solutions, solve(month, day).unwrap());
+            assert_eq!(raw_solutions.len() as u32, expected_count(month, day));
+        }
+    }
+
+    #[test]
+    fn no_flip_solutions_are_a_subset_of_the_flipped_ones() {
+        let mut no_flip_total = 0;
+        for ((month, day), raw_solutions) in all_dates().zip(CALENDAR_SOLUTIONS.iter()) {
+            let flipped: HashSet<[u64; 8]> = raw_solutions.iter().copied().collect();
+            let no_flip = find_raw_solutions(month, day, &SINGLE_SIDED_PIECE_PLACEMENTS);
+            assert!(no_flip.iter().all(|masks| flipped.contains(masks)), "{}/{}", month, day);
+            no_flip_total += no_flip.len();
+        }
+        assert!(no_flip_total < CALENDAR_SOLUTIONS.iter().map(Vec::len).sum());
+    }
+
+    #[test]
+    fn canonical_solutions_are_pairwise_inequivalent() {
+        for (month, day) in [(1, 1), (3, 15), (8, 25), (11, 25)] {
+            let solutions = solutions_for_date(month, day);
+            let raw_count = solutions.len();
+            let canonical = deduplicate_symmetric_solutions(solutions, month, day);
+            assert!(canonical.len() <= raw_count);
+            for (i, a) in canonical.iter().enumerate() {
+                for b in &canonical[i + 1..] {
+                    assert!((0..8).all(|rot_type| transform_board(&a.board, rot_type) != b.board));
+                }
+            }
+            assert_eq!(uniqueness_class_for_date(month, day).canonical_count as usize, canonical.len());
+        }
+    }
+
+    #[test]
+    fn streaming_closure_sees_every_solution_in_order() {
+        for (month, day) in [(1, 1), (3, 1), (11, 25)] {
+            let mut streamed = Vec::new();
+            let count = solve_streaming(month, day, |masks| streamed.push(*masks)).unwrap();
+            assert_eq!(count, expected_count(month, day));
+            assert_eq!(streamed, solve_raw(month, day).unwrap());
+        }
+
+        let mut called = false;
+        assert!(solve_streaming(2, 30, |_| called = true).is_err());
+        assert!(!called);
+    }
+
+    #[test]
+    fn first_empty_cell_search_finds_the_reference_solution_sets() {
+        /// 比較用の配置パターン - 形状データの配列を回転させる`rotate_and_flip`を使わず、埋まっているマスの座標 (行, 列) を直接回転・反転し、
+        /// 最小の行・列が0になるように平行移動してから、盤面内の全ての位置に置く
+        fn reference_placements(shape: &[Vec<u8>]) -> Vec<u64> {
+            let cells: Vec<(i32, i32)> = shape.iter().enumerate()
+                .flat_map(|(r, row)| row.iter().enumerate().filter(|&(_, &cell)| cell == 1).map(move |(c, _)| (r as i32, c as i32)))
+                .collect();
+            let mut placements = Vec::new();
+            for rot_type in 0..8 {
+                let moved: Vec<(i32, i32)> = cells.iter().map(|&(r, c)| {
+                    let c = if rot_type >= 4 { -c } else { c };
+                    (0..rot_type % 4).fold((r, c), |(r, c), _| (c, -r))
+                }).collect();
+                let (min_r, min_c) = (moved.iter().map(|&(r, _)| r).min().unwrap(), moved.iter().map(|&(_, c)| c).min().unwrap());
+                for dr in 0..7 {
+                    for dc in 0..7 {
+                        let placed: Vec<(i32, i32)> = moved.iter().map(|&(r, c)| (r - min_r + dr, c - min_c + dc)).collect();
+                        if placed.iter().all(|&(r, c)| r < 7 && c < 7) {
+                            placements.push(placed.iter().fold(0u64, |mask, &(r, c)| mask | 1 << (r * 7 + c)));
+                        }
+                    }
+                }
+            }
+            placements.sort_unstable();
+            placements.dedup();
+            placements
+        }
+
+        /// 比較用の素朴な探索 - ピースを番号順に1個ずつ、盤面のどこにでも置いてみて、島の大きさだけで枝刈りする
+        fn piece_order_search(
+            board: Board, piece_idx: usize, placements: &[Vec<u64>], used: &mut [u64; 8], sizes: &[usize; 8], solutions: &mut Vec<[u64; 8]>,
+        ) {
+            if piece_idx == 8 {
+                solutions.push(*used);
+                return;
+            }
+            for &mask in &placements[piece_idx] {
+                if board.overlaps(&Board(mask)) { continue; }
+                let next_board = board.union(&Board(mask));
+                if !judge_islands_for_sizes(next_board, &sizes[piece_idx + 1..], 7) { continue; }
+                used[piece_idx] = mask;
+                piece_order_search(next_board, piece_idx + 1, placements, used, sizes, solutions);
+            }
+            used[piece_idx] = 0;
+        }
+
+        // 探索の配置パターンは、独立に作った配置パターンと一致する
+        let placements: Vec<Vec<u64>> = get_initial_pieces().iter().map(|shape| reference_placements(shape)).collect();
+        assert_eq!(placements, compute_piece_placements());
+
+        let mut total = 0;
+        for (i, ((month, day), raw_solutions)) in all_dates().zip(CALENDAR_SOLUTIONS.iter()).enumerate() {
+            // 全解が正しい解で、重複がなく、個数が表と一致すれば、解の集合は表の解の集合そのものになる
+            let mut boards: Vec<Vec<Vec<i8>>> = raw_solutions.iter().map(|masks| masks_to_solution(masks, month, day).board).collect();
+            assert!(boards.iter().all(|board| is_valid_solution_board(month, day, board)));
+            boards.sort();
+            boards.dedup();
+            assert_eq!(boards.len() as u32, expected_count(month, day), "{}/{}", month, day);
+            total += boards.len();
+
+            // 全日付を素朴な探索で解くのは時間がかかるので、10日に1日だけ集合そのものを比べる
+            if i % 10 == 0 {
+                let sizes: [usize; 8] = std::array::from_fn(|piece_idx| placements[piece_idx][0].count_ones() as usize);
+                let mut reference = Vec::new();
+                piece_order_search(initial_board(month, day), 0, &placements, &mut [0; 8], &sizes, &mut reference);
+                let mut reference_boards: Vec<Vec<Vec<i8>>> = reference.iter().map(|masks| masks_to_solution(masks, month, day).board).collect();
+                reference_boards.sort();
+                assert_eq!(boards, reference_boards, "{}/{}", month, day);
+            }
+        }
+        assert_eq!(total, 24405);
+    }
+
+    #[test]
+    fn excluding_no_pieces_matches_the_full_search() {
+        for (month, day) in [(1, 1), (3, 1), (5, 14)] {
+            let mut subset: Vec<Vec<Vec<i8>>> = solutions_for_subset(month, day, &[]).unwrap().into_iter().map(|solution| solution.board).collect();
+            let mut full: Vec<Vec<Vec<i8>>> = solutions_for_date(month, day).into_iter().map(|solution| solution.board).collect();
+            subset.sort();
+            full.sort();
+            assert_eq!(subset, full);
+        }
+        assert!(solutions_for_subset(1, 1, &[0]).is_err());
+        assert!(solutions_for_subset(1, 1, &[9]).is_err());
+    }
+
+    #[test]
+    fn random_solutions_are_reproducible_and_valid() {
+        for (month, day) in [(1, 1), (11, 25)] {
+            let samples: Vec<Solution> = (0..8).map(|seed| random_solution(month, day, seed, compute_piece_placements()).unwrap()).collect();
+            for (seed, sample) in samples.iter().enumerate() {
+                assert_eq!(random_solution(month, day, seed as u64, compute_piece_placements()).as_ref(), Some(sample));
+                assert!(is_valid_solution_board(month, day, &sample.board));
+            }
+            // 解が複数ある日付では、シードによって異なる解が選ばれる
+            assert!(samples.iter().any(|sample| sample.board != samples[0].board));
+        }
+
+        // ピースを裏返せない盤面では、解の無い1月14日は`None`になり、解が1つだけの1月1日はどのシードでもその解になる
+        let single_sided = &SINGLE_SIDED_PIECE_PLACEMENTS;
+        assert_eq!(random_solution(1, 14, 42, single_sided), None);
+        let unique = find_raw_solutions(1, 1, single_sided);
+        assert_eq!(unique.len(), 1);
+        let unique = masks_to_solution(&unique[0], 1, 1);
+        assert!((0..4).all(|seed| random_solution(1, 1, seed, single_sided).unwrap().board == unique.board));
+    }
+
+    #[test]
+    fn piece_infos_list_eight_pieces_covering_the_open_cells() {
+        let pieces = piece_infos();
+        assert_eq!(pieces.len(), 8);
+        assert_eq!(pieces.iter().map(|piece| piece.id).collect::<Vec<_>>(), (1..=8).collect::<Vec<_>>());
+        // 49マスから固定の穴6マスと日付の穴2マスを除いた41マス
+        assert_eq!(pieces.iter().map(|piece| piece.size as usize).sum::<usize>(), 41);
+        for (piece, shape) in pieces.iter().zip(get_initial_pieces()) {
+            assert_eq!(piece.shape, shape);
+            assert_eq!(piece.is_size_6, piece.size == 6);
+        }
+        assert_eq!(pieces.iter().filter(|piece| piece.is_size_6).count(), 1);
+    }
+
+    #[cfg(feature = "dlx")]
+    #[test]
+    fn dlx_finds_the_same_solution_set_on_every_date() {
+        // DLXも同じ配置パターンから行を作るので、これは探索部分だけの検算になる
+        // 配置パターン自体は`first_empty_cell_search_finds_the_reference_solution_sets`で独立に作ったものと比べている
+        for (month, day) in all_dates() {
+            let mut dlx_solutions: Vec<Vec<u64>> = dlx::exact_covers(initial_board(month, day).0, compute_piece_placements());
+            let mut backtracking: Vec<Vec<u64>> = solve_raw(month, day).unwrap().iter().map(|masks| masks.to_vec()).collect();
+            dlx_solutions.sort_unstable();
+            backtracking.sort_unstable();
+            assert_eq!(dlx_solutions, backtracking, "{}月{}日", month, day);
+        }
+    }
+
+    #[test]
+    fn repeated_solves_produce_byte_identical_output() {
+        for (month, day) in [(1, 1), (8, 25), (11, 25)] {
+            let first = solutions_for_date(month, day);
+            let second = solutions_for_date(month, day);
+            assert_eq!(first, second);
+            let encode = |solutions: &[Solution]| solutions.iter().map(|solution| encode_board_rle(&solution.board)).collect::<Vec<_>>().join(",");
+            assert_eq!(encode(&first).into_bytes(), encode(&second).into_bytes());
+            assert_eq!(encode_solution_set(&solve_raw(month, day).unwrap()), encode_solution_set(&solve_raw(month, day).unwrap()));
+        }
+
+        // キャッシュを使わずに作り直した配置パターンも、毎回同じ昇順の並びになる
+        for shape in get_initial_pieces() {
+            let placements = shape_placements(&shape);
+            assert_eq!(placements, shape_placements(&shape));
+            assert!(placements.windows(2).all(|pair| pair[0] < pair[1]));
+        }
+    }
+
+    #[test]
+    fn all_date_counts_cover_the_calendar_with_known_counts() {
+        assert_eq!(DATE_COUNTS.len(), 366);
+        for (date_count, (month, day)) in DATE_COUNTS.iter().zip(all_dates()) {
+            assert_eq!((date_count.month, date_count.day), (month, day));
+            assert_eq!(date_count.count, expected_count(month, day), "{}月{}日", month, day);
+        }
+        let count_of = |month: u32, day: u32| DATE_COUNTS.iter().find(|c| (c.month, c.day) == (month, day)).unwrap().count;
+        // 盤面の右端や下端に接する配置が必要な日付、解が最も少ない日付、最も多い日付
+        assert_eq!(count_of(3, 1), 17);
+        assert_eq!(count_of(7, 30), 51);
+        assert_eq!(count_of(11, 25), 155);
+        assert_eq!(count_of(10, 6), 7);
+        assert_eq!(count_of(1, 25), 216);
+        assert_eq!(DATE_COUNTS.iter().map(|c| c.count).sum::<u32>(), 24405);
+    }
+
+    #[test]
+    fn date_strings_parse_in_every_accepted_format() {
+        assert_eq!(parse_date_str("3-15"), Ok((3, 15)));
+        assert_eq!(parse_date_str("03-15"), Ok((3, 15)));
+        assert_eq!(parse_date_str("2024-03-15"), Ok((3, 15)));
+        assert_eq!(parse_date_str("2024-02-29"), Ok((2, 29)));
+
+        for malformed in ["13-01", "3/15", "", "3-", "-15", "3-15-", "a-15", "2024-3-15-1", "99999999999-1"] {
+            assert!(parse_date_str(malformed).is_err(), "{:?}", malformed);
+        }
+        assert_eq!(parse_date_str("13-01"), Err(invalid_date_message(13, 1)));
+    }
+
+    #[test]
+    fn every_piece_has_a_distinct_color() {
+        let colors: Vec<&str> = (1..=8).map(piece_color).collect();
+        assert!(colors.iter().all(|color| !color.is_empty()));
+        assert_eq!(colors.iter().collect::<HashSet<_>>().len(), 8);
+        assert_eq!(colors, PIECE_COLORS.iter().map(|&(name, _)| name).collect::<Vec<_>>());
+        assert_eq!(piece_color(0), "");
+        assert_eq!(piece_color(9), "");
+    }
+
+    #[test]
+    fn every_date_is_solvable_in_the_default_layout() {
+        let unsolvable: Vec<(u32, u32)> = all_dates().filter(|&(month, day)| !is_date_solvable(month, day).unwrap()).collect();
+        assert_eq!(unsolvable, vec![]);
+        assert!(all_dates().all(|(month, day)| is_date_solvable(month, day).unwrap()));
+    }
+
+    #[test]
+    fn ascii_rendering_matches_the_known_solution() {
+        // 5月14日の探索順で最初の解
+        let board = solutions_for_date(5, 14).remove(0).board;
+        let expected = "\
+A A A B . B #
+A A A B B B #
+C C C F F F F
+G G C C F E .
+G D D D H E E
+G D H H H E E
+G D H # # # #";
+        assert_eq!(render_board_ascii(&board).unwrap(), expected);
+
+        assert!(render_board_ascii(&board[..6]).is_err());
+        let mut wide = board.clone();
+        wide[3].push(1);
+        assert!(render_board_ascii(&wide).is_err());
+        assert!(render_board_ascii(&[]).is_err());
+    }
+
+    #[test]
+    fn fixed_array_search_matches_a_vec_accumulator_search() {
+        let all_piece_placements = compute_piece_placements();
+        let piece_sizes: Vec<usize> = all_piece_placements.iter().map(|placements| placements[0].count_ones() as usize).collect();
+        let cell_placements = cell_placement_index(all_piece_placements);
+        for (month, day) in [(1, 1), (3, 1), (5, 14), (11, 25), (12, 31)] {
+            // 解を`Vec<u64>`として集める汎用の探索は、配置を試す順序が同じなので、解も同じ順に見つかる
+            let mut vec_solutions = Vec::new();
+            find_solutions_indexed_in_rows(initial_board(month, day), &mut [0; 8], &cell_placements, &piece_sizes, 7, &mut vec_solutions);
+            let array_solutions: Vec<Vec<u64>> = find_raw_solutions(month, day, all_piece_placements).iter().map(|masks| masks.to_vec()).collect();
+            assert_eq!(array_solutions, vec_solutions, "{}月{}日", month, day);
+        }
+    }
+
+    #[test]
+    fn bitmask_solutions_fill_the_whole_board() {
+        for (month, day) in [(1, 1), (5, 14), (11, 25)] {
+            let solutions = bitmask_solutions(month, day);
+            assert_eq!(solutions.len() as u32, expected_count(month, day));
+            for solution in &solutions {
+                assert_eq!(solution.pieces.iter().fold(solution.holes, |board, &mask| board | mask), 0x1FFFFFFFFFFFF);
+                // ピース同士も穴とも重ならないので、マス数の合計もちょうど49になる
+                assert_eq!(solution.pieces.iter().map(|mask| mask.count_ones()).sum::<u32>() + solution.holes.count_ones(), 49);
+            }
+        }
+    }
+
+    #[test]
+    fn time_budget_reports_whether_the_search_finished() {
+        let full = solutions_for_date(11, 25);
+
+        // 制限時間0では最初の時刻確認で打ち切られ、見つかった解は全解の先頭部分になる
+        let timed_out = solutions_within_budget(11, 25, 0.0);
+        assert!(!timed_out.complete);
+        assert!(timed_out.solutions.len() < full.len());
+        assert_eq!(timed_out.solutions[..], full[..timed_out.solutions.len()]);
+
+        let finished = solutions_within_budget(11, 25, 1e9);
+        assert!(finished.complete);
+        assert_eq!(finished.solutions, full);
+    }
+
+    #[test]
+    fn shape_pruning_never_discards_a_solution() {
+        // 島の大きさだけで枝刈りする探索と、島の形でも枝刈りする標準の探索は、全日付で同じ個数の解を見つける
+        let all_piece_placements = compute_piece_placements();
+        let piece_sizes: Vec<usize> = all_piece_placements.iter().map(|placements| placements[0].count_ones() as usize).collect();
+        let cell_placements = cell_placement_index(all_piece_placements);
+        for date_count in DATE_COUNTS.iter() {
+            let mut size_pruned = Vec::new();
+            find_solutions_indexed_in_rows(initial_board(date_count.month, date_count.day), &mut [0; 8], &cell_placements, &piece_sizes, 7, &mut size_pruned);
+            assert_eq!(size_pruned.len() as u32, date_count.count, "{}月{}日", date_count.month, date_count.day);
+        }
+
+        // 形の枝刈りは、大きさは合っていてもピースの形にならない島（T字の5マス）を手詰まりとする
+        let mut board = Board(FULL_BOARD_MASK);
+        for (r, c) in [(0, 0), (0, 1), (0, 2), (1, 1), (2, 1)] { board.0 &= !(1u64 << (r * 7 + c)); }
+        assert!(judge_islands_for_sizes(board, &[5], 7));
+        assert!(!judge_connected_component(board, [5]));
+    }
+
+    #[test]
+    fn unsolvable_dates_depend_on_the_piece_orientations() {
+        let dates = |all_piece_placements: &[Vec<u64>]| -> Vec<(u32, u32)> {
+            unsolvable_dates_with(all_piece_placements).iter().map(|date| (date.month, date.day)).collect()
+        };
+        // 標準の盤面とピース集合では、全ての日付に解がある
+        assert_eq!(dates(compute_piece_placements()), vec![]);
+
+        // 裏返せないピースでは、80日に解が無くなる
+        let no_flip = dates(&SINGLE_SIDED_PIECE_PLACEMENTS);
+        assert_eq!(no_flip.len(), 80);
+        assert_eq!(no_flip[..4], [(1, 14), (1, 18), (1, 19), (2, 12)]);
+        assert_eq!(no_flip[no_flip.len() - 3..], [(12, 2), (12, 18), (12, 19)]);
+        for &(month, day) in no_flip.iter().step_by(10) {
+            assert!(find_raw_solutions(month, day, &SINGLE_SIDED_PIECE_PLACEMENTS).is_empty());
+        }
+        let solvable = all_dates().find(|date| !no_flip.contains(date)).unwrap();
+        assert!(!find_raw_solutions(solvable.0, solvable.1, &SINGLE_SIDED_PIECE_PLACEMENTS).is_empty());
+    }
+
+    #[test]
+    fn hole_positions_match_the_holes_of_real_solutions() {
+        for ((month, day), raw_solutions) in all_dates().zip(CALENDAR_SOLUTIONS.iter()).step_by(5) {
+            let holes = BoardLayout::default().hole_positions(month, day);
+            let fixed: Vec<(u8, u8)> = FIXED_HOLES.iter().map(|&(r, c)| (r as u8, c as u8)).collect();
+            assert_eq!(holes.fixed, fixed);
+            for masks in raw_solutions {
+                let board = masks_to_solution(masks, month, day).board;
+                let cells_with = |value: i8| -> Vec<(u8, u8)> {
+                    (0..7).flat_map(|r| (0..7).map(move |c| (r, c))).filter(|&(r, c)| board[r][c] == value).map(|(r, c)| (r as u8, c as u8)).collect()
+                };
+                let mut date_holes = vec![holes.month_cell, holes.day_cell];
+                date_holes.sort();
+                assert_eq!(cells_with(-1), date_holes);
+                assert_eq!(cells_with(0), holes.fixed);
+            }
+        }
+    }
+
+    #[test]
+    fn extreme_dates_are_valid_and_ordered() {
+        let (hardest, easiest) = solution_count_extremes(&DATE_COUNTS).unwrap();
+        assert!(hardest.count <= easiest.count);
+        assert!(is_valid_date(hardest.month, hardest.day));
+        assert!(is_valid_date(easiest.month, easiest.day));
+        assert_eq!((hardest.month, hardest.day, hardest.count), (10, 6, 7));
+        assert_eq!((easiest.month, easiest.day, easiest.count), (1, 25, 216));
+        assert!(DATE_COUNTS.iter().all(|c| hardest.count <= c.count && c.count <= easiest.count));
+        assert!(solution_count_extremes(&[]).is_none());
+
+        // 同数の日付が複数あれば、最難・最易のどちらもカレンダー上で早い日付を選ぶ
+        let tied = [(2, 1, 5), (1, 3, 5), (1, 9, 9), (1, 4, 9)].map(|(month, day, count)| DateCount { month, day, count });
+        let (hardest, easiest) = solution_count_extremes(&tied).unwrap();
+        assert_eq!(((hardest.month, hardest.day), (easiest.month, easiest.day)), ((1, 3), (1, 4)));
+    }
+
+    #[test]
+    fn built_in_piece_set_tiles_the_board_exactly() {
+        assert_eq!(validate_piece_set(), Ok(()));
+
+        let mut pieces = get_initial_pieces();
+        pieces[1] = vec![vec![1, 1, 1, 1]];
+        assert!(validate_pieces(&pieces).is_err());
+        // マス数の合計が41のままでも、サイズ6のピースが無ければ通らない
+        let mut pieces = get_initial_pieces();
+        pieces[0] = vec![vec![1, 1, 1, 1, 1]];
+        pieces.push(vec![vec![1]]);
+        assert!(validate_pieces(&pieces).is_err());
+        // 盤面に収まらないピースは置けない
+        let mut pieces = get_initial_pieces();
+        pieces[1] = vec![vec![1, 1, 1, 1, 1, 1, 1, 1, 1]];
+        pieces[2] = vec![vec![1]];
+        assert!(validate_pieces(&pieces).is_err());
+
+        // 配置パターンの数は、向きごとに盤面内の全ての位置へ平行移動した数と一致する
+        // 2x3の長方形は縦横の2つの向きでそれぞれ5x6通り、空の行・列で囲まれた1マスは49通り
+        for shape in get_initial_pieces() {
+            assert_eq!(shape_placements(&shape).len(), translated_placement_count(&shape));
+        }
+        assert_eq!(translated_placement_count(&get_initial_pieces()[0]), 60);
+        let padded = vec![vec![0, 0, 0], vec![0, 1, 0], vec![0, 0, 0]];
+        assert_eq!((shape_placements(&padded).len(), translated_placement_count(&padded)), (49, 49));
+    }
+
+    #[test]
+    fn empty_board_tilings_filtered_by_date_give_each_dates_solutions() {
+        let tilings = empty_board_tilings();
+        assert!(!tilings.is_empty());
+
+        // 残った2マスのビットマスクごとに置き方を分けておく
+        let mut tilings_by_gaps: HashMap<u64, Vec<Vec<Vec<i8>>>> = HashMap::new();
+        for tiling in tilings {
+            let gaps = (0..49).filter(|&i| tiling.board[i / 7][i % 7] == -1).fold(0u64, |gaps, i| gaps | (1u64 << i));
+            assert_eq!(gaps.count_ones(), 2);
+            tilings_by_gaps.entry(gaps).or_default().push(tiling.board);
+        }
+        for ((month, day), raw_solutions) in all_dates().zip(CALENDAR_SOLUTIONS.iter()) {
+            let gaps = [month_cell(month), day_cell(day)].iter().fold(0u64, |gaps, &(r, c)| gaps | (1u64 << (r * 7 + c)));
+            let mut filtered = tilings_by_gaps.remove(&gaps).unwrap_or_default();
+            let mut expected: Vec<Vec<Vec<i8>>> = raw_solutions.iter().map(|masks| masks_to_solution(masks, month, day).board).collect();
+            filtered.sort();
+            expected.sort();
+            assert_eq!(filtered, expected, "{}月{}日", month, day);
+        }
+    }
+
+    #[test]
+    fn piece_set_without_a_size_6_piece_is_solved_without_panicking() {
+        // 2x3の長方形を、P字の5マスと1マスのピースに分けた集合
+        let mut pieces = get_initial_pieces();
+        pieces[0] = vec![vec![1, 1], vec![1, 1], vec![1, 0]];
+        pieces.push(vec![vec![1]]);
+        assert_eq!(find_size_6_piece(&pieces), None);
+        assert!(validate_pieces(&pieces).is_err());
+
+        let piece_counts: Vec<(Vec<Vec<u8>>, u32)> = pieces.into_iter().map(|shape| (shape, 1)).collect();
+        let solutions = solutions_with_piece_counts(5, 14, &piece_counts).unwrap();
+        assert!(solutions.iter().all(|solution| solution.board.iter().flatten().filter(|&&cell| cell > 0).count() == 41));
+
+        // 5月14日の最初の解の長方形は、P字と1マスに4通りに分けられる
+        let first = solutions_for_date(5, 14).remove(0).board;
+        let splits = solutions.iter().filter(|solution| {
+            first.iter().flatten().zip(solution.board.iter().flatten())
+                .all(|(&original, &split)| if original == 1 { split == 1 || split == 9 } else { split == original })
+        }).count();
+        assert_eq!(splits, 4);
+    }
+
+    #[test]
+    fn explicit_default_piece_set_matches_the_standard_solver() {
+        let default_set: Vec<(Vec<Vec<u8>>, u32)> = get_initial_pieces().into_iter().map(|shape| (shape, 1)).collect();
+        for (month, day) in [(1, 1), (3, 1), (5, 14), (11, 25)] {
+            let mut custom = solutions_with_piece_counts(month, day, &default_set).unwrap();
+            let mut standard = solutions_for_date(month, day);
+            custom.sort_by(|a, b| a.board.cmp(&b.board));
+            standard.sort_by(|a, b| a.board.cmp(&b.board));
+            assert_eq!(custom, standard, "{}月{}日", month, day);
+        }
+
+        // 1マス足りない集合と、1マス多すぎる集合はどちらも盤面をちょうど埋められない
+        let mut too_small = default_set.clone();
+        too_small[0].0 = vec![vec![1, 1], vec![1, 1], vec![1, 0]];
+        assert!(solutions_with_piece_counts(1, 1, &too_small).is_err());
+        let mut too_large = default_set;
+        too_large.push((vec![vec![1]], 1));
+        assert!(solutions_with_piece_counts(1, 1, &too_large).is_err());
+    }
+
+    #[test]
+    fn subset_sum_pruning_matches_the_hardcoded_size_rule() {
+        /// 一般化する前の枝刈り - 島の大きさは5の倍数か、サイズ6のピースが未使用なら「5の倍数 + 6」でなければならない
+        fn hardcoded_rule_holds(board: Board, is_size_6_piece_used: bool, uf: &mut UnionFind) -> bool {
+            uf.reset();
+            for i in (0..49).filter(|&i| (board.0 >> i) & 1 == 0) {
+                if (i + 1) % 7 != 0 && (board.0 >> (i + 1)) & 1 == 0 { uf.union(i, i + 1); }
+                if i < 42 && (board.0 >> (i + 7)) & 1 == 0 { uf.union(i, i + 7); }
+            }
+            uf.groups().filter(|&(root, _)| (board.0 >> root) & 1 == 0)
+                .all(|(_, size)| size % 5 == 0 || (!is_size_6_piece_used && size >= 6 && (size - 6) % 5 == 0))
+        }
+        fn count_with_hardcoded_rule(board: Board, used: &mut [u64; 8], cell_placements: &[Vec<(usize, u64)>], size_6_piece_index: usize, uf: &mut UnionFind) -> u32 {
+            let Some(cell) = first_empty_cell(board) else { return 1 };
+            let mut count = 0;
+            for &(piece_idx, mask) in &cell_placements[cell] {
+                if used[piece_idx] != 0 || board.overlaps(&Board(mask)) { continue; }
+                let next_board = board.union(&Board(mask));
+                used[piece_idx] = mask;
+                if hardcoded_rule_holds(next_board, used[size_6_piece_index] != 0, uf) {
+                    count += count_with_hardcoded_rule(next_board, used, cell_placements, size_6_piece_index, uf);
+                }
+                used[piece_idx] = 0;
+            }
+            count
+        }
+
+        // `DATE_COUNTS`は`count_raw_solutions`（部分和による枝刈り）で数えた個数
+        let cell_placements = cell_placement_index(compute_piece_placements());
+        let size_6_piece_index = find_size_6_piece(&get_initial_pieces()).unwrap();
+        let mut uf = UnionFind::new(49);
+        for date_count in DATE_COUNTS.iter() {
+            let start_board = initial_board(date_count.month, date_count.day);
+            let hardcoded = count_with_hardcoded_rule(start_board, &mut [0; 8], &cell_placements, size_6_piece_index, &mut uf);
+            assert_eq!(date_count.count, hardcoded, "{}月{}日", date_count.month, date_count.day);
+        }
+    }
+
+    #[test]
+    fn solution_svg_is_well_formed_with_one_path_per_piece() {
+        /// 要素の開始タグと終了タグが正しく入れ子になっていて、属性値の引用符が閉じているかを調べる
+        fn is_well_formed_xml(xml: &str) -> bool {
+            let mut open_tags = Vec::new();
+            let mut rest = xml;
+            while let Some(start) = rest.find('<') {
+                if !rest[..start].trim().is_empty() && open_tags.is_empty() { return false; }
+                let Some(end) = rest[start..].find('>') else { return false };
+                let tag = &rest[start + 1..start + end];
+                if !tag.matches('"').count().is_multiple_of(2) { return false; }
+                let name = tag.trim_start_matches('/').split(|ch: char| ch.is_whitespace() || ch == '/').next().unwrap_or("");
+                if name.is_empty() { return false; }
+                if let Some(closing) = tag.strip_prefix('/') {
+                    if open_tags.pop() != Some(closing.trim()) { return false; }
+                } else if !tag.ends_with('/') {
+                    open_tags.push(name);
+                } else if open_tags.is_empty() {
+                    return false;
+                }
+                rest = &rest[start + end + 1..];
+                if open_tags.is_empty() { return rest.trim().is_empty(); }
+            }
+            false
+        }
+
+        for (month, day) in [(1, 1), (5, 14), (11, 25)] {
+            let solution = solutions_for_date(month, day).remove(0);
+            let svg = board_to_svg(&solution.board, 40).unwrap();
+            assert!(svg.starts_with("<svg ") && svg.ends_with("</svg>"));
+            assert!(is_well_formed_xml(&svg));
+            assert!(svg.contains(r#"viewBox="0 0 280 280""#));
+            assert_eq!(svg.matches("<path ").count(), 8);
+            for piece_id in 1..=8 {
+                assert_eq!(svg.matches(&format!(r#"data-piece="{}""#, piece_id)).count(), 1);
+            }
+            assert_eq!(svg.matches(r#"class="date-hole""#).count(), 2);
+        }
+
+        // 自前の検査関数が崩れたXMLを見抜けること
+        assert!(!is_well_formed_xml("<svg><path d=\"M 0 0\"></svg>"));
+        assert!(!is_well_formed_xml("<svg><rect x=\"0/></svg>"));
+        assert!(board_to_svg(&vec![vec![0; 7]; 6], 40).is_err());
+    }
+
+    #[test]
+    fn stepping_a_session_yields_the_one_shot_solutions() {
+        for (month, day, max_nodes) in [(1, 1, 1), (5, 14, 3), (11, 25, 7)] {
+            let mut session = SolverSession::new(month, day).unwrap();
+            let mut stepped = Vec::new();
+            let mut steps = 0;
+            while !session.search.is_finished() {
+                stepped.extend(session.advance(max_nodes));
+                steps += 1;
+            }
+            assert!(steps > 1, "{}月{}日", month, day);
+            assert_eq!(stepped, solve_raw(month, day).unwrap(), "{}月{}日", month, day);
+            assert_eq!(session.collected, stepped);
+            // 探索を終えた後は新たな解を返さない
+            assert!(session.advance(max_nodes).is_empty());
+        }
+    }
+
+    #[test]
+    fn center_groups_sum_to_the_total_count() {
+        let center_bit = 3 * 7 + 3;
+        for ((month, day), raw_solutions) in all_dates().zip(CALENDAR_SOLUTIONS.iter()) {
+            if initial_board(month, day).get(3, 3) {
+                assert_eq!(day, 11);
+                assert!(group_solutions_by_piece_at(month, day, [3, 3]).is_err());
+                continue;
+            }
+            // 各解で中心を覆う配置はちょうど1つ
+            let mut per_piece = [0u32; 8];
+            for masks in raw_solutions {
+                let covering: Vec<usize> = (0..8).filter(|&piece_idx| (masks[piece_idx] >> center_bit) & 1 == 1).collect();
+                assert_eq!(covering.len(), 1);
+                per_piece[covering[0]] += 1;
+            }
+            assert_eq!(per_piece.iter().sum::<u32>(), expected_count(month, day));
+
+            if [(1, 1), (8, 25), (11, 25)].contains(&(month, day)) {
+                let groups = group_solutions_by_piece_at(month, day, [3, 3]).unwrap();
+                for (piece_id, group) in &groups {
+                    assert_eq!(group.len() as u32, per_piece[(*piece_id - 1) as usize]);
+                }
+                assert_eq!(groups.iter().map(|(_, group)| group.len() as u32).sum::<u32>(), expected_count(month, day));
+            }
+        }
+    }
+
+    #[test]
+    fn date_ranges_walk_the_calendar_including_month_ends() {
+        let dates = |counts: &[DateCount]| counts.iter().map(|c| (c.month, c.day)).collect::<Vec<_>>();
+
+        let week = count_solutions_in_range((3, 10), (3, 16)).unwrap();
+        assert_eq!(dates(&week), (10..=16).map(|day| (3, day)).collect::<Vec<_>>());
+        assert!(week.iter().all(|c| c.count == expected_count(c.month, c.day)));
+
+        // 2月は29日まで（うるう年を含めて全日付を扱う）
+        let across = count_solutions_in_range((2, 27), (3, 2)).unwrap();
+        assert_eq!(dates(&across), vec![(2, 27), (2, 28), (2, 29), (3, 1), (3, 2)]);
+        assert!(across.iter().all(|c| c.count == expected_count(c.month, c.day)));
+        assert_eq!(dates(&count_solutions_in_range((4, 30), (5, 1)).unwrap()), vec![(4, 30), (5, 1)]);
+
+        assert_eq!(count_solutions_in_range((3, 15), (3, 15)).unwrap().len(), 1);
+        assert!(count_solutions_in_range((3, 16), (3, 15)).is_err());
+        assert!(count_solutions_in_range((3, 1), (2, 29)).is_err());
+    }
+
+    #[test]
+    fn histogram_buckets_cover_every_date() {
+        let histogram = count_histogram(&DATE_COUNTS);
+        assert_eq!(histogram.buckets.values().sum::<u32>(), 366);
+        assert_eq!(histogram.buckets.iter().map(|(&count, &dates)| count * dates).sum::<u32>(), 24405);
+        assert_eq!(histogram.buckets.get(&0), None);
+        assert_eq!((histogram.min, histogram.max), (7, 216));
+        assert!((histogram.mean - 24405.0 / 366.0).abs() < 1e-9);
+
+        let empty = count_histogram(&[]);
+        assert!(empty.buckets.is_empty());
+        assert_eq!((empty.min, empty.max, empty.mean), (0, 0, 0.0));
+    }
+
+    #[test]
+    fn reused_union_find_behaves_like_a_fresh_one() {
+        let mut uf = UnionFind::new(49);
+        for i in 0..48 { uf.union(i, i + 1); }
+        assert_eq!(uf.groups().count(), 1);
+        uf.reset();
+        assert!(uf.groups().eq((0..49).map(|i| (i, 1))));
+        // 領域を確保し直さずに要素数を変えても、新しく作ったものと同じく全要素が別々のグループになる
+        uf.union(0, 1);
+        uf.reset_with_len(56);
+        assert!(uf.groups().eq((0..56).map(|i| (i, 1))));
+        uf.reset_with_len(7);
+        assert!(uf.groups().eq((0..7).map(|i| (i, 1))));
+
+        // 毎回新しいUnion-Find木で島の大きさを求めて判定する、比較用の実装
+        let fresh_judge = |board: Board, remaining_sizes: &[usize]| {
+            let mut fresh = UnionFind::new(49);
+            for i in (0..49).filter(|&i| !board.get(i / 7, i % 7)) {
+                if i % 7 != 6 && !board.get(i / 7, i % 7 + 1) { fresh.union(i, i + 1); }
+                if i < 42 && !board.get(i / 7 + 1, i % 7) { fresh.union(i, i + 7); }
+            }
+            let mut reachable_sums = 1u64;
+            for &size in remaining_sizes { reachable_sums |= reachable_sums << size; }
+            fresh.groups().filter(|&(root, _)| !board.get(root / 7, root % 7)).all(|(_, size)| (reachable_sums >> size) & 1 == 1)
+        };
+
+        // 行数の異なる盤面を交互に判定しても、前の盤面の状態は残らない
+        let mut rng = XorShift64::new(7);
+        for _ in 0..500 {
+            let board = Board((rng.next() | rng.next()) & FULL_BOARD_MASK);
+            let tall_board = Board(rng.next() & ((1u64 << 56) - 1));
+            judge_islands_for_sizes(tall_board, &[5, 5, 6], 8);
+            assert_eq!(judge_islands_for_sizes(board, &[5, 5, 6], 7), fresh_judge(board, &[5, 5, 6]));
+            // 形でも枝刈りする`judge_connected_component`は、大きさだけの判定より厳しいか同じになる
+            let promising = judge_connected_component(board, [5, 5, 6]);
+            assert!(!promising || fresh_judge(board, &[5, 5, 6]));
+            assert_eq!(judge_connected_component(board, [5, 5, 6]), promising);
+        }
+    }
+
+    #[test]
+    fn unreachable_cells_find_a_walled_off_notch() {
+        for (month, day) in all_dates() {
+            assert!(unreachable_cells(initial_board(month, day).0, compute_piece_placements()).is_empty());
+        }
+
+        // 標準の固定の穴に加えて (0, 1)・(1, 0)・(1, 1) を塞ぎ、左上の角 (0, 0) を1マスだけの切り欠きにした架空の盤面
+        const NOTCHED_FIXED_HOLES: [(usize, usize); 9] = [(0, 6), (1, 6), (6, 3), (6, 4), (6, 5), (6, 6), (0, 1), (1, 0), (1, 1)];
+        let layout = BoardLayout { fixed_holes: &NOTCHED_FIXED_HOLES, ..BoardLayout::dragon_fjord() };
+        let board = layout.initial_board(2, 3);
+        assert_eq!(unreachable_cells(board.0, compute_piece_placements()), vec![(0, 0)]);
+        assert!(reachable_cells(board.0, compute_piece_placements()) & 1 == 0);
+        assert!(solutions_for_layout(&layout, 2, 3).is_empty());
+    }
+
+    #[test]
+    fn each_layout_opens_the_two_date_holes_at_its_own_cells() {
+        let date_holes = |layout: &BoardLayout, month: u32, day: u32| -> Vec<(usize, usize)> {
+            let solutions = solutions_for_layout(layout, month, day);
+            assert!(!solutions.is_empty());
+            let mut holes: Vec<Vec<(usize, usize)>> = solutions.iter().map(|solution| {
+                (0..49).filter(|&i| solution.board[i / 7][i % 7] == -1).map(|i| (i / 7, i % 7)).collect()
+            }).collect();
+            holes.dedup();
+            assert_eq!(holes.len(), 1);
+            holes.remove(0)
+        };
+
+        let dragon_fjord = BoardLayout::from_name("dragon_fjord").unwrap();
+        assert_eq!(date_holes(&dragon_fjord, 3, 15), vec![(0, 2), (4, 0)]);
+        assert_eq!(solutions_for_layout(&dragon_fjord, 3, 15), solutions_for_date(3, 15));
+
+        // 月・日・固定の穴の座標を左右反転したレイアウトでは、日付の穴も反転した位置に開き、解の個数は変わらない
+        let mirror = |(r, c): (usize, usize)| (r, 6 - c);
+        const MIRRORED_FIXED_HOLES: [(usize, usize); 6] = [(0, 0), (1, 0), (6, 3), (6, 2), (6, 1), (6, 0)];
+        let mirrored = BoardLayout {
+            fixed_holes: &MIRRORED_FIXED_HOLES,
+            month_cells: dragon_fjord.month_cells.map(mirror),
+            day_cells: dragon_fjord.day_cells.map(mirror),
+        };
+        assert_eq!(date_holes(&mirrored, 3, 15), vec![(0, 4), (4, 6)]);
+        assert_eq!(date_holes(&mirrored, 12, 31), vec![(1, 1), (6, 4)]);
+        assert_eq!(solutions_for_layout(&mirrored, 3, 15).len(), solutions_for_date(3, 15).len());
+
+        // 実物の座標を確かめていない盤面の名前は受け付けない
+        assert!(BoardLayout::from_name("right_aligned").is_none());
+        assert!(BoardLayout::from_name("jarring_words").is_none());
+        assert_eq!(check_variant_name("dragon_fjord"), Ok(()));
+        assert!(check_variant_name("jarring_words").is_err());
+    }
+
+    #[test]
+    fn solution_sets_round_trip_through_the_compact_encoding() {
+        for (month, day) in [(1, 1), (3, 1), (5, 14), (11, 25)] {
+            let raw_solutions = solve_raw(month, day).unwrap();
+            let encoded = encode_solution_set(&raw_solutions);
+            assert_eq!(decode_solution_set(month, day, &encoded), Ok(raw_solutions));
+        }
+
+        let raw_solutions = solve_raw(1, 1).unwrap();
+        let encoded = encode_solution_set(&raw_solutions);
+        // 別の日付の盤面には合わない
+        assert!(decode_solution_set(1, 2, &encoded).is_err());
+        // 版番号が違う文字列は読まない
+        let mut bytes = decode_base64(&encoded).unwrap();
+        bytes[0] = SOLUTION_SET_FORMAT_VERSION + 1;
+        assert!(decode_solution_set(1, 1, &encode_base64(&bytes)).is_err());
+
+        // 2つのピースの配置を入れ替えると、盤面は重なりなく埋まっていてもピースの形が合わない
+        let mut swapped = raw_solutions[0];
+        let (a, b) = (0..8).flat_map(|a| (a + 1..8).map(move |b| (a, b)))
+            .find(|&(a, b)| compute_piece_placements()[a].binary_search(&swapped[b]).is_err()).unwrap();
+        swapped.swap(a, b);
+        assert!(decode_solution_set(1, 1, &encode_solution_set(&[swapped])).unwrap_err().contains("ピース"));
+    }
+
+    #[test]
+    fn uniqueness_class_separates_single_and_many_solution_dates() {
+        // 穴の配置を保つ対称変換がないので、解の少ない日付でも多い日付でも個数はそのまま
+        let fewest = uniqueness_class_for_date(10, 6);
+        assert_eq!((fewest.raw_count, fewest.canonical_count, fewest.is_unique), (7, 7, false));
+        let many = uniqueness_class_for_date(11, 25);
+        assert_eq!((many.raw_count, many.canonical_count, many.is_unique), (155, 155, false));
+
+        // 標準のピース集合には解がただ1つの日付がないので、裏返せないピースの盤面で、解が1つの1月1日と解の無い1月14日を使う
+        let single_sided_solutions = |month: u32, day: u32| -> Vec<Solution> {
+            find_raw_solutions(month, day, &SINGLE_SIDED_PIECE_PLACEMENTS).iter().map(|masks| masks_to_solution(masks, month, day)).collect()
+        };
+        let unique = uniqueness_class_of(single_sided_solutions(1, 1), 1, 1);
+        assert_eq!((unique.raw_count, unique.canonical_count, unique.is_unique), (1, 1, true));
+        let none = uniqueness_class_of(single_sided_solutions(1, 14), 1, 14);
+        assert_eq!((none.raw_count, none.canonical_count, none.is_unique), (0, 0, false));
+    }
+
+    #[test]
+    fn cell_piece_counts_sum_to_the_solution_count_on_open_cells() {
+        for ((month, day), raw_solutions) in all_dates().zip(CALENDAR_SOLUTIONS.iter()) {
+            let counts = cell_piece_counts(raw_solutions);
+            let holes = initial_board(month, day);
+            for (r, row) in counts.iter().enumerate() {
+                for (c, piece_counts) in row.iter().enumerate() {
+                    let total: u32 = piece_counts.iter().sum();
+                    if holes.get(r, c) {
+                        assert_eq!(piece_counts, &[0; 8]);
+                    } else {
+                        assert_eq!(total, expected_count(month, day), "{}月{}日 ({}, {})", month, day, r, c);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,39 @@
+// コマンドラインから日付を指定して、全解の盤面を表示するネイティブのバイナリ
+// 使い方: cargo run --release --features cli -- <月> <日>
+
+use std::process::ExitCode;
+
+/// 盤面の1マスを表示用の1文字にする - ピースは1〜8、日付の穴は'.'、固定の穴は'#'
+fn cell_char(cell: i8) -> char {
+    match cell {
+        -1 => '.',
+        0 => '#',
+        piece_id => char::from(b'0' + piece_id as u8),
+    }
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let (Some(month), Some(day)) = (args.first().and_then(|a| a.parse().ok()), args.get(1).and_then(|a| a.parse().ok())) else {
+        eprintln!("使い方: solver <月> <日>");
+        return ExitCode::FAILURE;
+    };
+
+    let solutions = match solver::solve(month, day) {
+        Ok(solutions) => solutions,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    println!("{}月{}日: {}個の解", month, day, solutions.len());
+    for (i, solution) in solutions.iter().enumerate() {
+        println!();
+        println!("解{}:", i + 1);
+        for row in &solution.board {
+            println!("{}", row.iter().map(|&cell| cell_char(cell)).collect::<String>());
+        }
+    }
+    ExitCode::SUCCESS
+}